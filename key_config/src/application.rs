@@ -22,7 +22,7 @@ where
 struct Item {
     id: String,
     name: String,
-    keys: Keys,
+    binding: Binding,
 }
 
 #[derive(Debug)]
@@ -31,6 +31,38 @@ struct Category {
     items: Vec<Item>,
 }
 
+const MACROS_CATEGORY: &str = "マクロ";
+const LAUNCHERS_CATEGORY: &str = "外部ツール";
+
+/// Reads the `name` of each entry of macros.json or launchers.json as a category.
+fn named_entries(
+    path: impl AsRef<std::path::Path>,
+    category: &str,
+    key_map: &KeyMap,
+) -> Result<Option<Category>, Error> {
+    let entries: serde_json::Value = match from_file(path) {
+        Ok(v) => v,
+        Err(Error::FileNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let entries = entries.as_object().ok_or(Error::InvalidData)?;
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    let items = entries
+        .iter()
+        .map(|(id, m)| Item {
+            id: id.clone(),
+            name: m.get("name").and_then(|n| n.as_str()).unwrap_or(id).to_string(),
+            binding: key_map.get(id).cloned().unwrap_or_default(),
+        })
+        .collect();
+    Ok(Some(Category {
+        name: category.to_string(),
+        items,
+    }))
+}
+
 #[derive(Debug)]
 struct KeyTable(Vec<Category>);
 
@@ -38,16 +70,18 @@ impl KeyTable {
     fn from_file(
         mmd_map_path: impl AsRef<std::path::Path>,
         order_path: impl AsRef<std::path::Path>,
+        macros_path: impl AsRef<std::path::Path>,
+        launchers_path: impl AsRef<std::path::Path>,
         key_map_path: impl AsRef<std::path::Path>,
     ) -> Result<Self, Error> {
         let mmd_map: serde_json::Value = from_file(mmd_map_path)?;
         let order: serde_json::Value = from_file(order_path)?;
-        let key_map: serde_json::Value = match from_file(&key_map_path) {
+        let key_map: KeyMap = match from_file(&key_map_path) {
             Ok(v) => v,
             Err(Error::FileNotFound(_)) => {
                 let key_map = KeyMap::default();
                 to_file(key_map_path, &key_map)?;
-                serde_json::to_value(&key_map).unwrap()
+                key_map
             }
             Err(e) => return Err(e),
         };
@@ -80,20 +114,11 @@ impl KeyTable {
                     .and_then(|a| a.as_array())
                     .and_then(|a| a[0].as_str())
                     .ok_or(Error::InvalidData)?;
-                let keys = key_map
-                    .get(id)
-                    .and_then(|v| v.as_array())
-                    .and_then(|a| {
-                        a.iter()
-                            .map(|v| v.as_u64().map(|v| v as u32))
-                            .collect::<Option<Vec<_>>>()
-                    })
-                    .map(|a| Keys::from_slice(&a))
-                    .unwrap_or_default();
+                let binding = key_map.get(id).cloned().unwrap_or_default();
                 v.push(Item {
                     id: id.to_string(),
                     name: name.to_string(),
-                    keys,
+                    binding,
                 });
             }
             table.push(Category {
@@ -101,16 +126,31 @@ impl KeyTable {
                 items: v,
             });
         }
+        table.extend(named_entries(macros_path, MACROS_CATEGORY, &key_map)?);
+        table.extend(named_entries(launchers_path, LAUNCHERS_CATEGORY, &key_map)?);
         Ok(Self(table))
     }
 
+    /// Writes the bindings of the table, keeping the bindings in the file of the actions not in the table
+    /// such as the entries added to mmd_map.json by hand.
     fn to_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
         let mut v = KeyMap::new();
+        if let Ok(current) = from_file::<KeyMap>(&path) {
+            let ids = self
+                .0
+                .iter()
+                .flat_map(|cat| &cat.items)
+                .map(|item| item.id.as_str())
+                .collect::<std::collections::HashSet<_>>();
+            for (id, binding) in current.into_iter().filter(|(id, _)| !ids.contains(id.as_str())) {
+                v.insert(id, binding);
+            }
+        }
         for elem in self.0.iter().flat_map(|cat| &cat.items).filter_map(|item| {
-            if item.keys.is_empty() {
+            if item.binding.keys.is_empty() {
                 None
             } else {
-                Some((&item.id, &item.keys))
+                Some((&item.id, &item.binding))
             }
         }) {
             v.insert(elem.0, elem.1.clone());
@@ -130,12 +170,36 @@ impl KeyTable {
 
     #[inline]
     fn get(&self, category: usize, item: usize) -> &Keys {
-        &(self.0)[category].items[item].keys
+        &(self.0)[category].items[item].binding.keys
     }
 
     #[inline]
     fn set_keys(&mut self, category: usize, item: usize, keys: Option<Keys>) {
-        (self.0)[category].items[item].keys = keys.unwrap_or_default();
+        (self.0)[category].items[item].binding.keys = keys.unwrap_or_default();
+    }
+
+    #[inline]
+    fn is_macros(&self, category: usize) -> bool {
+        (self.0)[category].name == MACROS_CATEGORY
+    }
+
+    fn rename_macro(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        category: usize,
+        item: usize,
+        name: String,
+    ) -> Result<(), Error> {
+        let mut macros: serde_json::Value = from_file(&path)?;
+        let target = &mut (self.0)[category].items[item];
+        let m = macros
+            .get_mut(&target.id)
+            .and_then(|m| m.as_object_mut())
+            .ok_or(Error::InvalidData)?;
+        m.insert("name".into(), name.clone().into());
+        to_file(path, &macros)?;
+        target.name = name;
+        Ok(())
     }
 }
 
@@ -217,6 +281,7 @@ pub struct Application {
     side_menu: SideMenu,
     shortcut_list: ShortcutList,
     editor: Box<Editor>,
+    name_editor: Box<NameEditor>,
     key_table: KeyTable,
     popup_menu: PopupMenu,
 }
@@ -257,7 +322,13 @@ impl Application {
                 );
             }
         }
-        let key_table = KeyTable::from_file("mmd_map.json", "order.json", "key_map.json")?;
+        let key_table = KeyTable::from_file(
+            "mmd_map.json",
+            "order.json",
+            "macros.json",
+            "launchers.json",
+            "key_map.json",
+        )?;
         let layout = calc_layout(settings.window_size);
         let mut side_menu = SideMenu::new(&main_window, layout.side_menu.position, layout.side_menu.size)?;
         key_table.iter().for_each(|cat| side_menu.push(&cat.name));
@@ -271,8 +342,9 @@ impl Application {
         key_table[0]
             .items
             .iter()
-            .for_each(|item| shortcut_list.push(&item.name, &item.keys));
+            .for_each(|item| shortcut_list.push(&item.name, &item.binding.keys));
         let editor = Editor::new(shortcut_list.handle())?;
+        let name_editor = NameEditor::new(shortcut_list.handle())?;
         let mut app = Box::new(Self {
             settings,
             main_window,
@@ -280,6 +352,7 @@ impl Application {
             shortcut_list,
             key_table,
             editor,
+            name_editor,
             popup_menu: PopupMenu::new(),
         });
         unsafe {
@@ -307,7 +380,7 @@ impl Application {
     fn update_shortcut_list(&mut self) {
         let category = self.side_menu.current_index();
         for (index, item) in self.key_table[category].items.iter().enumerate() {
-            if item.keys.is_empty() {
+            if item.binding.keys.is_empty() {
                 self.shortcut_list.set_dup(index, None);
                 continue;
             }
@@ -315,7 +388,7 @@ impl Application {
                 .key_table
                 .iter()
                 .flat_map(|cat| &cat.items)
-                .filter(|i| i.id != item.id && !i.keys.is_empty() && i.keys == item.keys)
+                .filter(|i| i.id != item.id && !i.binding.keys.is_empty() && i.binding.keys == item.binding.keys)
                 .map(|i| i.name.as_str())
                 .collect::<Vec<_>>();
             if dup.is_empty() {
@@ -351,6 +424,7 @@ impl wita::EventHandler for Box<Application> {
 
     fn dpi_changed(&mut self, _: &wita::Window, _dpi: u32) {
         self.editor.resize();
+        self.name_editor.resize();
     }
 
     fn drop_files(&mut self, _: &wita::Window, paths: &[&std::path::Path], _: wita::PhysicalPosition<f32>) {
@@ -384,6 +458,8 @@ impl wita::EventHandler for Box<Application> {
 
 pub const WM_KEY_CONFIG_EDIT_APPLY: u32 = WM_APP + 10;
 pub const WM_KEY_CONFIG_EDIT_CANCEL: u32 = WM_APP + 11;
+pub const WM_KEY_CONFIG_RENAME_APPLY: u32 = WM_APP + 12;
+pub const WM_KEY_CONFIG_RENAME_CANCEL: u32 = WM_APP + 13;
 
 unsafe extern "system" fn main_window_proc(
     hwnd: HWND,
@@ -409,7 +485,7 @@ unsafe extern "system" fn main_window_proc(
                         if nlv.uNewState & LVIS_SELECTED != 0 {
                             app.shortcut_list.clear();
                             for item in app.key_table[app.side_menu.current_index()].items.iter() {
-                                app.shortcut_list.push(&item.name, &item.keys);
+                                app.shortcut_list.push(&item.name, &item.binding.keys);
                             }
                             app.update_shortcut_list();
                         }
@@ -473,10 +549,12 @@ unsafe extern "system" fn main_window_proc(
                                 y: nia.ptAction.y,
                             };
                             ClientToScreen(app.shortcut_list.handle(), &mut pt);
+                            let category = app.side_menu.current_index();
                             app.popup_menu.track(
                                 &app.main_window,
-                                app.side_menu.current_index(),
+                                category,
                                 nia.iItem as _,
+                                app.key_table.is_macros(category),
                                 wita::ScreenPosition::new(pt.x, pt.y),
                             );
                         }
@@ -507,11 +585,43 @@ unsafe extern "system" fn main_window_proc(
             LRESULT(1)
         }
         WM_COMMAND => {
-            if (wparam.0 & 0xffff) as u32 == IDM_MENU_DETACH {
-                app.update_keys_to_file(app.popup_menu.category(), app.popup_menu.item(), None);
+            match (wparam.0 & 0xffff) as u32 {
+                IDM_MENU_DETACH => {
+                    app.update_keys_to_file(app.popup_menu.category(), app.popup_menu.item(), None);
+                }
+                IDM_MENU_RENAME => {
+                    let category = app.popup_menu.category();
+                    let item = app.popup_menu.item();
+                    if let Some(rc) = app.shortcut_list.name_rect(item) {
+                        app.name_editor
+                            .begin(&rc, category, item, &app.key_table[category].items[item].name);
+                    }
+                }
+                _ => {}
             }
             LRESULT(0)
         }
+        WM_KEY_CONFIG_RENAME_APPLY => {
+            if let Some(ret) = app.name_editor.end() {
+                match app
+                    .key_table
+                    .rename_macro("macros.json", ret.category, ret.item, ret.name)
+                {
+                    Ok(_) => {
+                        if ret.category == app.side_menu.current_index() {
+                            let name = &app.key_table[ret.category].items[ret.item].name;
+                            app.shortcut_list.set_name(ret.item, name);
+                        }
+                    }
+                    Err(e) => log::error!("{}", e),
+                }
+            }
+            LRESULT(0)
+        }
+        WM_KEY_CONFIG_RENAME_CANCEL => {
+            app.name_editor.cancel();
+            LRESULT(0)
+        }
         WM_KEY_CONFIG_EDIT_APPLY => {
             if app.editor.is_visible() {
                 if let Some(ret) = app.editor.end() {
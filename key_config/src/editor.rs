@@ -1,6 +1,6 @@
 use crate::*;
 
-fn theme_font(hwnd: HWND) -> windows::core::Result<HFONT> {
+pub fn theme_font(hwnd: HWND) -> windows::core::Result<HFONT> {
     unsafe {
         let theme_name = to_wchar("TEXTSTYLE");
         let theme = OpenThemeData(hwnd, PWSTR(theme_name.as_ptr() as _));
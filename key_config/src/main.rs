@@ -3,6 +3,7 @@
 mod application;
 mod editor;
 mod error;
+mod name_editor;
 mod old_key_map;
 mod popup_menu;
 mod shortcut_list;
@@ -15,6 +16,7 @@ use key_map::*;
 use log4rs::append::{console, console::ConsoleAppender, file::FileAppender};
 use log4rs::config::{Appender, Config, Root};
 use log4rs::encode::pattern::PatternEncoder;
+use name_editor::*;
 use old_key_map::OldKeyMap;
 use popup_menu::*;
 use shortcut_list::*;
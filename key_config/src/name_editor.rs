@@ -0,0 +1,148 @@
+use crate::*;
+
+pub struct RenameResult {
+    pub category: usize,
+    pub item: usize,
+    pub name: String,
+}
+
+pub struct NameEditor {
+    hwnd: HWND,
+    font: Option<HFONT>,
+    target: Option<(usize, usize)>,
+}
+
+impl NameEditor {
+    pub fn new(parent: HWND) -> Result<Box<Self>, Error> {
+        let class_name = to_wchar("EDIT");
+        unsafe {
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                PWSTR(class_name.as_ptr() as _),
+                PWSTR::default(),
+                WS_CHILD | WS_BORDER | WINDOW_STYLE(ES_AUTOHSCROLL as _),
+                0,
+                0,
+                1,
+                1,
+                parent,
+                HMENU(0),
+                HINSTANCE(0),
+                std::ptr::null(),
+            );
+            let font = theme_font(hwnd);
+            if let Ok(font) = font.as_ref() {
+                SendMessageW(hwnd, WM_SETFONT, WPARAM(font.0 as _), LPARAM(0));
+            }
+            let editor = Box::new(Self {
+                hwnd,
+                font: font.ok(),
+                target: None,
+            });
+            SetWindowSubclass(hwnd, Some(proc), 0, editor.as_ref() as *const _ as _);
+            Ok(editor)
+        }
+    }
+
+    #[inline]
+    pub fn begin(&mut self, rc: &RECT, category: usize, item: usize, name: &str) {
+        unsafe {
+            MoveWindow(self.hwnd, rc.left, rc.top, rc.right - rc.left, rc.bottom - rc.top, true);
+            set_window_text(self.hwnd, name);
+            ShowWindow(self.hwnd, SW_SHOW);
+            SetFocus(self.hwnd);
+            SendMessageW(self.hwnd, EM_SETSEL, WPARAM(0), LPARAM(-1));
+            self.target = Some((category, item));
+        }
+    }
+
+    #[inline]
+    pub fn end(&mut self) -> Option<RenameResult> {
+        unsafe {
+            let (category, item) = self.target.take()?;
+            let name = get_window_text(self.hwnd).trim().to_string();
+            SetFocus(GetParent(self.hwnd));
+            ShowWindow(self.hwnd, SW_HIDE);
+            if name.is_empty() {
+                None
+            } else {
+                Some(RenameResult { category, item, name })
+            }
+        }
+    }
+
+    #[inline]
+    pub fn cancel(&mut self) {
+        unsafe {
+            self.target = None;
+            SetFocus(GetParent(self.hwnd));
+            ShowWindow(self.hwnd, SW_HIDE);
+        }
+    }
+
+    #[inline]
+    pub fn resize(&mut self) {
+        unsafe {
+            let font = theme_font(self.hwnd);
+            if let Ok(font) = font {
+                SendMessageW(self.hwnd, WM_SETFONT, WPARAM(font.0 as _), LPARAM(0));
+                if let Some(font) = self.font {
+                    DeleteObject(font);
+                }
+                self.font = Some(font);
+            }
+        }
+    }
+}
+
+impl Drop for NameEditor {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(font) = self.font {
+                DeleteObject(font);
+            }
+        }
+    }
+}
+
+unsafe extern "system" fn proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    data_ptr: usize,
+) -> LRESULT {
+    let editor = (data_ptr as *mut NameEditor).as_mut().unwrap();
+    match msg {
+        WM_KEYDOWN if wparam.0 == VK_RETURN.0 as usize => {
+            PostMessageW(
+                GetParent(GetParent(editor.hwnd)),
+                WM_KEY_CONFIG_RENAME_APPLY,
+                WPARAM(0),
+                LPARAM(0),
+            );
+            LRESULT(0)
+        }
+        WM_KEYDOWN if wparam.0 == VK_ESCAPE.0 as usize => {
+            PostMessageW(
+                GetParent(GetParent(editor.hwnd)),
+                WM_KEY_CONFIG_RENAME_CANCEL,
+                WPARAM(0),
+                LPARAM(0),
+            );
+            LRESULT(0)
+        }
+        WM_CHAR if wparam.0 == '\r' as usize || wparam.0 == 0x1b => LRESULT(0),
+        WM_KILLFOCUS if editor.target.is_some() => {
+            PostMessageW(
+                GetParent(GetParent(editor.hwnd)),
+                WM_KEY_CONFIG_RENAME_APPLY,
+                WPARAM(0),
+                LPARAM(0),
+            );
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
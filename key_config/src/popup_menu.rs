@@ -1,6 +1,7 @@
 use crate::*;
 
 pub const IDM_MENU_DETACH: u32 = 10;
+pub const IDM_MENU_RENAME: u32 = 11;
 
 pub struct PopupMenu {
     menu: HMENU,
@@ -14,6 +15,8 @@ impl PopupMenu {
             let menu = CreatePopupMenu();
             let text = to_wchar("解除");
             AppendMenuW(menu, MF_STRING, IDM_MENU_DETACH as _, PWSTR(text.as_ptr() as _));
+            let text = to_wchar("名前の変更");
+            AppendMenuW(menu, MF_STRING, IDM_MENU_RENAME as _, PWSTR(text.as_ptr() as _));
             Self {
                 menu,
                 category: 0,
@@ -23,10 +26,22 @@ impl PopupMenu {
     }
 
     #[inline]
-    pub fn track(&mut self, window: &wita::Window, category: usize, item: usize, pt: wita::ScreenPosition) {
+    pub fn track(
+        &mut self,
+        window: &wita::Window,
+        category: usize,
+        item: usize,
+        renamable: bool,
+        pt: wita::ScreenPosition,
+    ) {
         unsafe {
             self.category = category;
             self.item = item;
+            EnableMenuItem(
+                self.menu,
+                IDM_MENU_RENAME,
+                if renamable { MF_ENABLED } else { MF_GRAYED },
+            );
             TrackPopupMenu(
                 self.menu,
                 TPM_LEFTALIGN | TPM_VCENTERALIGN,
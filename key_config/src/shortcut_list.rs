@@ -106,6 +106,28 @@ impl ShortcutList {
         }
     }
 
+    #[inline]
+    pub fn name_rect(&self, index: usize) -> Option<RECT> {
+        unsafe {
+            let mut rc = RECT {
+                left: LVIR_LABEL as _,
+                top: 0,
+                ..Default::default()
+            };
+            let ret = SendMessageW(
+                self.hwnd,
+                LVM_GETSUBITEMRECT,
+                WPARAM(index as _),
+                LPARAM(&mut rc as *mut _ as _),
+            );
+            if ret == LRESULT(0) {
+                None
+            } else {
+                Some(rc)
+            }
+        }
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         unsafe {
@@ -150,6 +172,22 @@ impl ShortcutList {
         }
     }
 
+    #[inline]
+    pub fn set_name(&mut self, index: usize, name: impl AsRef<str>) {
+        unsafe {
+            let text = to_wchar(name.as_ref());
+            let item = LVITEMW {
+                iItem: index as _,
+                iSubItem: 0,
+                mask: LVIF_TEXT,
+                pszText: PWSTR(text.as_ptr() as _),
+                cchTextMax: text.len() as _,
+                ..Default::default()
+            };
+            SendMessageW(self.hwnd, LVM_SETITEMW, WPARAM(0), LPARAM(&item as *const _ as _));
+        }
+    }
+
     #[inline]
     pub fn set_dup(&mut self, index: usize, text: Option<&str>) {
         unsafe {
@@ -154,8 +154,143 @@ impl Default for Keys {
     }
 }
 
+/// Auto-repeat settings of a held key.
+///
+/// All durations are in milliseconds. The interval between repeats shrinks linearly
+/// from `interval` to `min_interval` over `acceleration` after `delay` has passed.
+/// The repeats are picked from the key repeat of Windows, so `delay` and the intervals
+/// have no effect below the repeat delay and rate set in Windows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Repeat {
+    pub enabled: bool,
+    pub delay: u32,
+    pub interval: u32,
+    pub min_interval: u32,
+    pub acceleration: u32,
+}
+
+impl Repeat {
+    #[inline]
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the interval between repeats after the key has been held for `held` ms,
+    /// or `None` if the key must not repeat yet.
+    pub fn interval_at(&self, held: u32) -> Option<u32> {
+        if !self.enabled || held < self.delay {
+            return None;
+        }
+        let min_interval = self.min_interval.min(self.interval);
+        if self.acceleration == 0 {
+            return Some(self.interval);
+        }
+        let t = (held - self.delay).min(self.acceleration) as u64;
+        let d = (self.interval - min_interval) as u64 * t / self.acceleration as u64;
+        Some(self.interval - d as u32)
+    }
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay: 500,
+            interval: 100,
+            min_interval: 100,
+            acceleration: 0,
+        }
+    }
+}
+
+/// Settings of an action which is active only while the keys are held.
+///
+/// `release` is performed when the keys are released.
+/// Without `release`, the checked one of `restore` at the time of the press is performed on release.
+#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Momentary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restore: Vec<String>,
+}
+
+/// Multiplies the amount of an action while `key` is held.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Multiplier {
+    pub key: u32,
+    pub factor: i32,
+}
+
+/// Keys bound to an action and the per-binding options.
+///
+/// A binding without options is written to key_map.json as the plain array of keys.
+#[derive(Clone, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Binding {
+    pub keys: Keys,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<Repeat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub momentary: Option<Momentary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frames: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub register: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<Multiplier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_edit: Option<bool>,
+}
+
+impl Binding {
+    #[inline]
+    pub fn has_options(&self) -> bool {
+        self != &Self::from(self.keys.clone())
+    }
+}
+
+impl From<Keys> for Binding {
+    #[inline]
+    fn from(keys: Keys) -> Self {
+        Self {
+            keys,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BindingRepr {
+    Keys(Keys),
+    Binding(Binding),
+}
+
+impl From<BindingRepr> for Binding {
+    #[inline]
+    fn from(src: BindingRepr) -> Self {
+        match src {
+            BindingRepr::Keys(keys) => keys.into(),
+            BindingRepr::Binding(binding) => binding,
+        }
+    }
+}
+
 #[derive(Debug)]
-pub struct KeyMap(HashMap<String, Keys>);
+pub struct KeyMap(HashMap<String, Binding>);
 
 impl KeyMap {
     pub fn new() -> Self {
@@ -168,32 +303,32 @@ impl KeyMap {
     }
 
     #[inline]
-    pub fn insert(&mut self, k: impl AsRef<str>, v: Keys) {
-        self.0.insert(k.as_ref().into(), v);
+    pub fn insert(&mut self, k: impl AsRef<str>, v: impl Into<Binding>) {
+        self.0.insert(k.as_ref().into(), v.into());
     }
 
     #[inline]
-    pub fn get(&self, k: impl AsRef<str>) -> Option<&Keys> {
+    pub fn get(&self, k: impl AsRef<str>) -> Option<&Binding> {
         self.0.get(k.as_ref())
     }
 
     #[inline]
-    pub fn get_mut(&mut self, k: impl AsRef<str>) -> Option<&mut Keys> {
+    pub fn get_mut(&mut self, k: impl AsRef<str>) -> Option<&mut Binding> {
         self.0.get_mut(k.as_ref())
     }
 
     #[inline]
-    pub fn iter(&self) -> std::collections::hash_map::Iter<String, Keys> {
+    pub fn iter(&self) -> std::collections::hash_map::Iter<String, Binding> {
         self.0.iter()
     }
 }
 
 impl std::iter::IntoIterator for KeyMap {
-    type Item = (String, Keys);
-    type IntoIter = std::collections::hash_map::IntoIter<String, Keys>;
+    type Item = (String, Binding);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Binding>;
 
     #[inline]
-    fn into_iter(self) -> std::collections::hash_map::IntoIter<String, Keys> {
+    fn into_iter(self) -> std::collections::hash_map::IntoIter<String, Binding> {
         self.0.into_iter()
     }
 }
@@ -205,7 +340,11 @@ impl serde::Serialize for KeyMap {
     {
         let mut map = serializer.serialize_map(Some(self.0.len()))?;
         for (k, v) in self.0.iter() {
-            map.serialize_entry(k, v)?;
+            if v.has_options() {
+                map.serialize_entry(k, v)?;
+            } else {
+                map.serialize_entry(k, &v.keys)?;
+            }
         }
         map.end()
     }
@@ -230,8 +369,8 @@ impl<'de> serde::Deserialize<'de> for KeyMap {
                 M: serde::de::MapAccess<'de>,
             {
                 let mut map = HashMap::with_capacity(access.size_hint().unwrap_or(0));
-                while let Some((key, value)) = access.next_entry()? {
-                    map.insert(key, value);
+                while let Some((key, value)) = access.next_entry::<String, BindingRepr>()? {
+                    map.insert(key, value.into());
                 }
                 Ok(KeyMap(map))
             }
@@ -309,8 +448,69 @@ mod tests {
         key_map.insert("Undo", Keys(vec![VK_CONTROL.0 as _, b'Z' as _]));
         key_map.insert("Redo", Keys(vec![VK_CONTROL.0 as _, VK_SHIFT.0 as _, b'Z' as _]));
         let ret: KeyMap = serde_json::from_str(&serde_json::to_string(&key_map).unwrap()).unwrap();
-        assert!(ret.get("Undo").unwrap() == &Keys(vec![VK_CONTROL.0 as _, b'Z' as _]));
-        assert!(ret.get("Redo").unwrap() == &Keys(vec![VK_CONTROL.0 as _, VK_SHIFT.0 as _, b'Z' as _]));
-        assert!(ret.get("Undo").unwrap() != &Keys(vec![VK_SHIFT.0 as _, b'Z' as _]));
+        assert!(ret.get("Undo").unwrap().keys == Keys(vec![VK_CONTROL.0 as _, b'Z' as _]));
+        assert!(ret.get("Redo").unwrap().keys == Keys(vec![VK_CONTROL.0 as _, VK_SHIFT.0 as _, b'Z' as _]));
+        assert!(ret.get("Undo").unwrap().keys != Keys(vec![VK_SHIFT.0 as _, b'Z' as _]));
+    }
+
+    #[test]
+    fn binding_test() {
+        let json = r#"{ "Undo": [17, 90], "KeyDelete": { "keys": [46], "repeat": { "enabled": false } } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        assert!(!key_map.get("Undo").unwrap().has_options());
+        let binding = key_map.get("KeyDelete").unwrap();
+        assert!(binding.keys == Keys(vec![VK_DELETE.0 as _]));
+        assert!(binding.repeat == Some(Repeat::disabled()));
+        let ret: serde_json::Value = serde_json::to_value(&key_map).unwrap();
+        assert!(ret["Undo"].is_array());
+        assert!(ret["KeyDelete"]["repeat"]["enabled"] == false);
+        assert!(ret["KeyDelete"].get("in_edit").is_none());
+    }
+
+    #[test]
+    fn momentary_test() {
+        let json = r#"{ "BoneMove": { "keys": [90], "momentary": { "restore": ["BoneRotate", "BoneSelect"] } } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        let momentary = key_map.get("BoneMove").unwrap().momentary.as_ref().unwrap();
+        assert!(momentary.release.is_none());
+        assert!(momentary.restore == vec!["BoneRotate".to_string(), "BoneSelect".to_string()]);
+        let ret: serde_json::Value = serde_json::to_value(&key_map).unwrap();
+        assert!(ret["BoneMove"]["momentary"].get("release").is_none());
+    }
+
+    #[test]
+    fn frames_test() {
+        let json = r#"{ "FrameJumpNext10": { "keys": [39], "frames": 5, "multiplier": { "key": 16, "factor": 10 } } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        let binding = key_map.get("FrameJumpNext10").unwrap();
+        assert!(binding.frames == Some(5));
+        assert!(binding.multiplier == Some(Multiplier { key: 16, factor: 10 }));
+        assert!(binding.has_options());
+        let json = r#"{ "MainPosYUp": { "keys": [38], "step": 0.5 } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        assert!(key_map.get("MainPosYUp").unwrap().step == Some(0.5));
+        let json = r#"{ "MorphEyePreset": { "keys": [49], "values": [0, 0.5, 1] } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        assert!(key_map.get("MorphEyePreset").unwrap().values == Some(vec![0.0, 0.5, 1.0]));
+        let json = r#"{ "ModelSelectorNext": { "keys": [78], "wrap": true }, "ModelSelectorSelect": { "keys": [77], "text": "Miku" } }"#;
+        let key_map: KeyMap = serde_json::from_str(json).unwrap();
+        assert!(key_map.get("ModelSelectorNext").unwrap().wrap == Some(true));
+        assert!(key_map.get("ModelSelectorSelect").unwrap().text.as_deref() == Some("Miku"));
+    }
+
+    #[test]
+    fn repeat_interval_test() {
+        let repeat = Repeat {
+            enabled: true,
+            delay: 300,
+            interval: 100,
+            min_interval: 20,
+            acceleration: 1000,
+        };
+        assert!(repeat.interval_at(200).is_none());
+        assert!(repeat.interval_at(300) == Some(100));
+        assert!(repeat.interval_at(800) == Some(60));
+        assert!(repeat.interval_at(5000) == Some(20));
+        assert!(Repeat::disabled().interval_at(5000).is_none());
     }
 }
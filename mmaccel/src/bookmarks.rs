@@ -0,0 +1,93 @@
+use crate::json_map::JsonMap;
+use crate::*;
+use std::path::PathBuf;
+
+#[derive(Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub frame: i32,
+}
+
+/// Frame bookmarks keyed by the project (.pmm) path and the bookmark number.
+#[derive(Debug, Default)]
+pub struct Bookmarks(JsonMap<String, Bookmark>);
+
+impl Bookmarks {
+    #[inline]
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self(JsonMap::from_file(path))
+    }
+
+    #[inline]
+    pub fn get(&self, project: &str, n: u32) -> Option<&Bookmark> {
+        self.0.get(project, n)
+    }
+
+    #[inline]
+    pub fn iter(&self, project: &str) -> impl Iterator<Item = (u32, &Bookmark)> {
+        self.0.iter_key(project)
+    }
+
+    /// Sets the frame of the bookmark `n`, keeping its name if it already exists.
+    pub fn set(&mut self, project: &str, n: u32, frame: i32) {
+        self.0.update(project.to_string(), n, |bookmark| Bookmark {
+            name: bookmark.map_or_else(|| format!("ブックマーク{}", n), |bookmark| bookmark.name),
+            frame,
+        });
+    }
+
+    #[inline]
+    pub fn next(&self, project: &str, frame: i32) -> Option<i32> {
+        self.iter(project).map(|(_, b)| b.frame).filter(|f| *f > frame).min()
+    }
+
+    #[inline]
+    pub fn prev(&self, project: &str, frame: i32) -> Option<i32> {
+        self.iter(project).map(|(_, b)| b.frame).filter(|f| *f < frame).max()
+    }
+}
+
+/// Extracts the project path from the title of MMD like `MikuMikuDance Ver9.32 [C:\motion\dance.pmm]`.
+///
+/// Returns an empty string for an unsaved project.
+fn project_from_title(title: &str) -> &str {
+    title
+        .rfind('[')
+        .and_then(|begin| {
+            let path = &title[begin + 1..];
+            path.find(']').map(|end| &path[..end])
+        })
+        .filter(|path| path.to_ascii_lowercase().ends_with(".pmm"))
+        .unwrap_or("")
+}
+
+#[inline]
+pub fn current_project(mmd_window: HWND) -> String {
+    project_from_title(&get_window_text(mmd_window)).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_title() {
+        assert!(project_from_title("MikuMikuDance Ver9.32 [C:\\motion\\dance.pmm]") == "C:\\motion\\dance.pmm");
+        assert!(project_from_title("MikuMikuDance Ver9.32").is_empty());
+    }
+
+    #[test]
+    fn next_prev() {
+        let path = std::env::temp_dir().join(format!("mmaccel_bookmarks_{}.json", std::process::id()));
+        let mut bookmarks = Bookmarks::from_file(&path);
+        let project = "dance.pmm";
+        for (n, frame) in [(1, 30), (2, 90), (3, 60)] {
+            bookmarks.set(project, n, frame);
+        }
+        std::fs::remove_file(&path).ok();
+        assert!(bookmarks.next(project, 30) == Some(60));
+        assert!(bookmarks.prev(project, 60) == Some(30));
+        assert!(bookmarks.next(project, 90).is_none());
+        assert!(bookmarks.prev("other.pmm", 90).is_none());
+    }
+}
@@ -1,7 +1,13 @@
 use crate::*;
-use handler::Handler;
+use bookmarks::*;
+use handler::{find_menu_item, is_edit, Handler, KillFocusMode, MACRO_TIMER_ID, SYNTHESIZED_KEY};
 use key_map::KeyMap;
-use mmd_map::MmdMap;
+use launchers::{Launchers, WM_LAUNCHER_EXITED};
+use macros::Macros;
+use mmd_map::{BookmarkOp, ItemKind, MmdMap, SnapshotOp};
+use recorder::Recorder;
+use snapshots::Snapshots;
+use std::collections::HashSet;
 use std::sync::{atomic, atomic::AtomicBool, Arc};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -9,6 +15,8 @@ pub enum MenuItem {
     LaunchConfig,
     RaiseTimerResolution(bool),
     KillFocusWithClick(bool),
+    PassUnboundKeys(bool),
+    RecordMacro(bool),
     Version,
 }
 
@@ -22,40 +30,140 @@ impl MenuCommand for MenuItem {
             _ if v == std::mem::discriminant(&Self::KillFocusWithClick(false)) => {
                 Self::KillFocusWithClick(item_type.as_with_check().unwrap())
             }
+            _ if v == std::mem::discriminant(&Self::PassUnboundKeys(false)) => {
+                Self::PassUnboundKeys(item_type.as_with_check().unwrap())
+            }
+            _ if v == std::mem::discriminant(&Self::RecordMacro(false)) => {
+                Self::RecordMacro(item_type.as_with_check().unwrap())
+            }
             _ if v == std::mem::discriminant(&Self::Version) => Self::Version,
             _ => unimplemented!(),
         }
     }
 }
 
+/// The number of command IDs for the bookmark submenu, indexed by the bookmark number.
+const BOOKMARK_MENU_IDS: u32 = 1000;
+
+/// The number of command IDs for the snapshot submenu, indexed by the snapshot.
+const SNAPSHOT_MENU_IDS: u32 = 1000;
+
+const KILL_FOCUS_MODES: [(KillFocusMode, &str); 3] = [
+    (KillFocusMode::Leave, "そのまま"),
+    (KillFocusMode::Commit, "確定する"),
+    (KillFocusMode::Revert, "元に戻す"),
+];
+
+/// The command IDs of the items in a submenu, starting from the first item.
+struct SubMenu {
+    menu: HMENU,
+    ids: std::ops::Range<u32>,
+}
+
+impl SubMenu {
+    /// Returns the index of the item if `command` is in this submenu.
+    #[inline]
+    fn index(&self, command: u32) -> Option<u32> {
+        if self.ids.contains(&command) {
+            Some(command - self.ids.start)
+        } else {
+            None
+        }
+    }
+}
+
 struct MmdWindow {
     window: HWND,
     sub_window: Option<HWND>,
     menu: Menu<MenuItem>,
+    bookmark_menu: SubMenu,
+    snapshot_menu: SubMenu,
+    kill_focus_menu: SubMenu,
 }
 
 impl MmdWindow {
     #[inline]
     fn new(window: HWND, settings: &Settings) -> Self {
-        Self {
+        let bookmark_menu = unsafe { CreatePopupMenu() };
+        let snapshot_menu = unsafe { CreatePopupMenu() };
+        let kill_focus_menu = unsafe { CreatePopupMenu() };
+        let mut menu = MenuBuilder::new(window, "MMAccel")
+            .item(&MenuItem::LaunchConfig, "キー設定")
+            .separator()
+            .with_check(
+                &MenuItem::RaiseTimerResolution(true),
+                "タイマーの精度を上げる",
+                settings.raise_timer_resolution,
+            )
+            .with_check(
+                &MenuItem::KillFocusWithClick(true),
+                "クリックで入力状態を解除",
+                settings.kill_focus_with_click,
+            )
+            .sub_menu("入力状態の解除時", kill_focus_menu)
+            .with_check(
+                &MenuItem::PassUnboundKeys(true),
+                "割り当てのないキーを渡す",
+                settings.pass_unbound_keys,
+            )
+            .separator()
+            .with_check(&MenuItem::RecordMacro(true), "マクロを記録", false)
+            .sub_menu("ブックマーク", bookmark_menu)
+            .sub_menu("スナップショット", snapshot_menu)
+            .separator()
+            .item(&MenuItem::Version, "バージョン情報")
+            .build();
+        let kill_focus_menu = SubMenu {
+            menu: kill_focus_menu,
+            ids: menu.reserve_ids(KILL_FOCUS_MODES.len() as _),
+        };
+        let bookmark_menu = SubMenu {
+            menu: bookmark_menu,
+            ids: menu.reserve_ids(BOOKMARK_MENU_IDS),
+        };
+        let snapshot_menu = SubMenu {
+            menu: snapshot_menu,
+            ids: menu.reserve_ids(SNAPSHOT_MENU_IDS),
+        };
+        for (id, (_, text)) in kill_focus_menu.ids.clone().zip(KILL_FOCUS_MODES.iter()) {
+            unsafe {
+                AppendMenuW(kill_focus_menu.menu, MF_STRING, id as _, *text);
+            }
+        }
+        let mmd_window = Self {
             window,
             sub_window: None,
-            menu: MenuBuilder::new(window, "MMAccel")
-                .item(&MenuItem::LaunchConfig, "キー設定")
-                .separator()
-                .with_check(
-                    &MenuItem::RaiseTimerResolution(true),
-                    "タイマーの精度を上げる",
-                    settings.raise_timer_resolution,
-                )
-                .with_check(
-                    &MenuItem::KillFocusWithClick(true),
-                    "クリックで入力状態を解除",
-                    settings.kill_focus_with_click,
-                )
-                .separator()
-                .item(&MenuItem::Version, "バージョン情報")
-                .build(),
+            menu,
+            bookmark_menu,
+            snapshot_menu,
+            kill_focus_menu,
+        };
+        mmd_window.check_kill_focus_mode(settings.kill_focus_mode);
+        mmd_window
+    }
+
+    /// Returns whether `hwnd` is the main or sub window of MMD or one of their controls.
+    fn contains(&self, hwnd: HWND) -> bool {
+        unsafe {
+            let parent = GetParent(hwnd);
+            hwnd == self.window
+                || parent == self.window
+                || Some(hwnd) == self.sub_window
+                || Some(parent) == self.sub_window
+        }
+    }
+
+    fn check_kill_focus_mode(&self, mode: KillFocusMode) {
+        let index = KILL_FOCUS_MODES.iter().position(|(m, _)| *m == mode).unwrap_or(0);
+        let ids = &self.kill_focus_menu.ids;
+        unsafe {
+            CheckMenuRadioItem(
+                self.kill_focus_menu.menu,
+                ids.start,
+                ids.end - 1,
+                ids.start + index as u32,
+                MF_BYCOMMAND.0,
+            );
         }
     }
 }
@@ -80,15 +188,67 @@ impl Drop for TimePeriod {
     }
 }
 
+/// Replaces the items of a submenu with `(command ID, text)`.
+fn rebuild_menu(menu: HMENU, items: impl IntoIterator<Item = (u32, String)>) {
+    unsafe {
+        while GetMenuItemCount(menu) > 0 {
+            DeleteMenu(menu, 0, MF_BYPOSITION);
+        }
+        let mut empty = true;
+        for (id, text) in items {
+            AppendMenuW(menu, MF_STRING, id as _, text);
+            empty = false;
+        }
+        if empty {
+            AppendMenuW(menu, MF_STRING | MF_GRAYED, 0, "（なし）");
+        }
+    }
+}
+
+/// Rebuilds the bookmark submenu with the bookmarks of the current project.
+fn update_bookmark_menu(menu: &SubMenu, bookmarks: &Bookmarks, project: &str) {
+    rebuild_menu(
+        menu.menu,
+        bookmarks
+            .iter(project)
+            .filter(|(n, _)| (*n as usize) < menu.ids.len())
+            .map(|(n, bookmark)| {
+                (
+                    menu.ids.start + n,
+                    format!("{}: {} ({})", n, bookmark.name, bookmark.frame),
+                )
+            }),
+    );
+}
+
+/// Rebuilds the snapshot submenu with the snapshots of all panels.
+fn update_snapshot_menu(menu: &SubMenu, handler: &Handler) {
+    rebuild_menu(
+        menu.menu,
+        menu.ids
+            .clone()
+            .zip(handler.snapshots().iter())
+            .map(|(id, (panel, _, snapshot))| {
+                let label = handler.panel_label(panel).unwrap_or_default();
+                (id, format!("{} {}", label, snapshot.name))
+            }),
+    );
+}
+
 fn version_info(hwnd: HWND) {
     let text = format!("MMAccel {}\nby LNSEAB", env!("CARGO_PKG_VERSION"));
     message_box(Some(hwnd), text, "", MB_OK);
 }
 
+/// `pass_keys` and `consume_keys` override `pass_unbound_keys` for each virtual key.
 #[derive(Debug, serde::Serialize)]
 struct Settings {
     raise_timer_resolution: bool,
     kill_focus_with_click: bool,
+    kill_focus_mode: KillFocusMode,
+    pass_unbound_keys: bool,
+    pass_keys: Vec<u32>,
+    consume_keys: Vec<u32>,
 }
 
 impl Settings {
@@ -112,6 +272,22 @@ impl Settings {
                         .get("kill_focus_with_click")
                         .and_then(|v| v.as_bool())
                         .unwrap_or(default.kill_focus_with_click),
+                    kill_focus_mode: obj
+                        .get("kill_focus_mode")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or(default.kill_focus_mode),
+                    pass_unbound_keys: obj
+                        .get("pass_unbound_keys")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(default.pass_unbound_keys),
+                    pass_keys: obj
+                        .get("pass_keys")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or(default.pass_keys),
+                    consume_keys: obj
+                        .get("consume_keys")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or(default.consume_keys),
                 })
             }
             Err(_) => None,
@@ -130,12 +306,20 @@ impl Default for Settings {
         Self {
             raise_timer_resolution: true,
             kill_focus_with_click: true,
+            kill_focus_mode: KillFocusMode::default(),
+            pass_unbound_keys: false,
+            pass_keys: vec![],
+            consume_keys: vec![],
         }
     }
 }
 
 const MMD_MAP_PATH: &str = "MMAccel/mmd_map.json";
 const KEY_MAP_PATH: &str = "MMAccel/key_map.json";
+const MACROS_PATH: &str = "MMAccel/macros.json";
+const LAUNCHERS_PATH: &str = "MMAccel/launchers.json";
+const BOOKMARKS_PATH: &str = "MMAccel/bookmarks.json";
+const SNAPSHOTS_PATH: &str = "MMAccel/snapshots.json";
 
 pub struct Context {
     module_path: std::path::PathBuf,
@@ -149,6 +333,8 @@ pub struct Context {
     latest_key_map: Arc<AtomicBool>,
     key_config: Option<HWND>,
     time_period: Option<TimePeriod>,
+    recorder: Option<Recorder>,
+    passing: HashSet<u32>,
 }
 
 impl Context {
@@ -165,7 +351,12 @@ impl Context {
             }
             m
         });
-        let handler = Handler::new(&mmd_map, key_map);
+        let macros = Macros::from_file(module_path.join(MACROS_PATH)).unwrap_or_default();
+        let launchers = Launchers::from_file(module_path.join(LAUNCHERS_PATH)).unwrap_or_default();
+        let bookmarks = Bookmarks::from_file(module_path.join(BOOKMARKS_PATH));
+        let snapshots = Snapshots::from_file(module_path.join(SNAPSHOTS_PATH));
+        let mut handler = Handler::new(&mmd_map, &macros, &launchers, bookmarks, snapshots, key_map);
+        handler.set_kill_focus_mode(settings.kill_focus_mode);
         let file_monitor = FileMonitor::new();
         let time_period = settings.raise_timer_resolution.then(|| TimePeriod::new(1));
         Ok(Self {
@@ -184,6 +375,8 @@ impl Context {
             latest_key_map: Arc::new(AtomicBool::new(true)),
             key_config: None,
             time_period,
+            recorder: None,
+            passing: HashSet::new(),
         })
     }
 
@@ -199,6 +392,14 @@ impl Context {
                         latest_key_map.store(false, atomic::Ordering::SeqCst);
                         PostMessageW(mmd_window, WM_APP, WPARAM(0), LPARAM(0));
                         log::debug!("update key_map.json");
+                    } else if path.file_name() == Some(std::ffi::OsStr::new("macros.json")) {
+                        latest_key_map.store(false, atomic::Ordering::SeqCst);
+                        PostMessageW(mmd_window, WM_APP, WPARAM(0), LPARAM(0));
+                        log::debug!("update macros.json");
+                    } else if path.file_name() == Some(std::ffi::OsStr::new("launchers.json")) {
+                        latest_key_map.store(false, atomic::Ordering::SeqCst);
+                        PostMessageW(mmd_window, WM_APP, WPARAM(0), LPARAM(0));
+                        log::debug!("update launchers.json");
                     }
                 });
             }
@@ -208,6 +409,34 @@ impl Context {
                     mmd_window.sub_window = Some(data.hwnd);
                 }
             }
+            WM_INITMENUPOPUP
+                if self
+                    .mmd_window
+                    .as_ref()
+                    .map_or(false, |mw| mw.bookmark_menu.menu.0 == data.wParam.0 as isize) =>
+            {
+                let mmd_window = self.mmd_window.as_ref().unwrap();
+                let project = current_project(mmd_window.window);
+                update_bookmark_menu(&mmd_window.bookmark_menu, self.handler.bookmarks(), &project);
+            }
+            WM_INITMENUPOPUP
+                if self
+                    .mmd_window
+                    .as_ref()
+                    .map_or(false, |mw| mw.snapshot_menu.menu.0 == data.wParam.0 as isize) =>
+            {
+                let mmd_window = self.mmd_window.as_ref().unwrap();
+                update_snapshot_menu(&mmd_window.snapshot_menu, &self.handler);
+            }
+            WM_SETFOCUS if is_edit(data.hwnd) => {
+                self.handler.edit_focused(data.hwnd);
+            }
+            WM_COMMAND => {
+                let caused = self.handler.take_caused_command((data.wParam.0 & 0xffff) as u32);
+                if let Some(recorder) = self.recorder.as_mut().filter(|_| !caused) {
+                    recorder.command(data.wParam, data.lParam);
+                }
+            }
             WM_DESTROY if self.mmd_window.as_ref().map_or(false, |mw| mw.window == data.hwnd) => {
                 if let Some(kc) = self.key_config {
                     unsafe {
@@ -239,7 +468,46 @@ impl Context {
     pub fn get_message(&mut self, data: &mut MSG) -> bool {
         match data.message {
             WM_COMMAND => {
+                let caused = self.handler.take_caused_command((data.wParam.0 & 0xffff) as u32);
+                if let Some(recorder) = self.recorder.as_mut().filter(|_| !caused) {
+                    recorder.command(data.wParam, data.lParam);
+                }
                 if let Some(mmd_window) = self.mmd_window.as_ref() {
+                    let command = (data.wParam.0 & 0xffff) as u32;
+                    let bookmark = mmd_window.bookmark_menu.index(command).filter(|_| data.lParam.0 == 0);
+                    if let Some(n) = bookmark {
+                        let id = self.mmd_map.iter().find_map(|(_, item)| match item.kind {
+                            ItemKind::Bookmark(_, id) => Some(id),
+                            _ => None,
+                        });
+                        if let Some(id) = id {
+                            let item = ItemKind::Bookmark(BookmarkOp::Go(n), id);
+                            self.handler.handle(item, mmd_window.window, mmd_window.sub_window);
+                        }
+                        return false;
+                    }
+                    let snapshot = mmd_window.snapshot_menu.index(command).filter(|_| data.lParam.0 == 0);
+                    if let Some(index) = snapshot {
+                        let target = self
+                            .handler
+                            .snapshots()
+                            .iter()
+                            .nth(index as _)
+                            .map(|(panel, n, _)| (panel, n));
+                        if let Some((panel, n)) = target {
+                            let item = ItemKind::Snapshot(SnapshotOp::Load, panel, n);
+                            self.handler.handle(item, mmd_window.window, mmd_window.sub_window);
+                        }
+                        return false;
+                    }
+                    let kill_focus = mmd_window.kill_focus_menu.index(command).filter(|_| data.lParam.0 == 0);
+                    if let Some(index) = kill_focus {
+                        let mode = KILL_FOCUS_MODES[index as usize].0;
+                        mmd_window.check_kill_focus_mode(mode);
+                        self.settings.kill_focus_mode = mode;
+                        self.handler.set_kill_focus_mode(mode);
+                        return false;
+                    }
                     match mmd_window.menu.recv_command(data.wParam) {
                         Some(MenuItem::LaunchConfig) => {
                             let path = self.module_path.join("MMAccel/key_config.exe");
@@ -276,44 +544,113 @@ impl Context {
                         Some(MenuItem::KillFocusWithClick(b)) => {
                             self.settings.kill_focus_with_click = b;
                         }
+                        Some(MenuItem::PassUnboundKeys(b)) => {
+                            self.settings.pass_unbound_keys = b;
+                        }
+                        Some(MenuItem::RecordMacro(true)) => {
+                            self.recorder = Some(Recorder::new(&self.mmd_map, mmd_window.window));
+                            log::debug!("start recording");
+                        }
+                        Some(MenuItem::RecordMacro(false)) => self.finish_recording(),
                         Some(MenuItem::Version) => version_info(mmd_window.window),
                         _ => {}
                     }
                 }
             }
+            WM_TIMER
+                if data.wParam.0 == MACRO_TIMER_ID
+                    && self.mmd_window.as_ref().map_or(false, |mw| mw.window == data.hwnd) =>
+            {
+                let mmd_window = self.mmd_window.as_ref().unwrap();
+                self.handler.tick(mmd_window.window, mmd_window.sub_window);
+                self.record_dispatched();
+                data.message = WM_NULL;
+                return true;
+            }
+            WM_LAUNCHER_EXITED if self.mmd_window.as_ref().map_or(false, |mw| mw.window == data.hwnd) => {
+                self.handler.launcher_exited(data.wParam.0);
+                data.message = WM_NULL;
+                return true;
+            }
+            WM_KEYDOWN | WM_SYSKEYDOWN if (data.lParam.0 & SYNTHESIZED_KEY) != 0 => {
+                self.handler.synthesized_key(data.wParam.0 as u32, true);
+            }
+            WM_KEYUP | WM_SYSKEYUP if (data.lParam.0 & SYNTHESIZED_KEY) != 0 => {
+                let vk = data.wParam.0 as u32;
+                if vk == VK_RETURN.0 as u32 && is_edit(data.hwnd) {
+                    self.handler.edit_committed(data.hwnd);
+                }
+                self.handler.synthesized_key(vk, false);
+            }
             WM_KEYDOWN | WM_SYSKEYDOWN => unsafe {
                 let mmd_window = self.mmd_window.as_ref().unwrap();
                 let main_window = mmd_window.window;
                 let sub_window = mmd_window.sub_window;
-                let cond = data.hwnd == main_window
-                    || GetParent(data.hwnd) == main_window
-                    || Some(data.hwnd) == sub_window
-                    || sub_window.map_or(false, |sw| GetParent(data.hwnd) == sw);
-                if cond {
-                    self.handler
-                        .key_down(data.wParam.0 as u32, main_window, sub_window, data.hwnd);
+                if mmd_window.contains(data.hwnd) {
+                    let vk = data.wParam.0 as u32;
+                    if vk == VK_RETURN.0 as u32 && is_edit(data.hwnd) {
+                        self.evaluate_edit(data.hwnd);
+                    }
+                    if (vk == VK_UP.0 as u32 || vk == VK_DOWN.0 as u32) && is_edit(data.hwnd) {
+                        let delta = if vk == VK_UP.0 as u32 { 1.0 } else { -1.0 };
+                        let coarse = GetKeyState(VK_SHIFT.0 as _) < 0;
+                        let fine = GetKeyState(VK_CONTROL.0 as _) < 0;
+                        if self
+                            .handler
+                            .scrub(data.hwnd, delta, coarse, fine, main_window, sub_window)
+                        {
+                            data.message = WM_NULL;
+                            return true;
+                        }
+                    }
+                    if self.is_passed(vk) {
+                        self.passing.insert(vk);
+                        return false;
+                    }
+                    let repeat = (data.lParam.0 >> 30) & 1 != 0;
+                    self.handler.key_down(vk, repeat, main_window, sub_window, data.hwnd);
+                    self.record_dispatched();
                     return true;
                 }
             },
-            WM_KEYUP | WM_SYSKEYUP => unsafe {
+            WM_KEYUP | WM_SYSKEYUP => {
                 let mmd_window = self.mmd_window.as_ref().unwrap();
                 let main_window = mmd_window.window;
                 let sub_window = mmd_window.sub_window;
-                let cond = data.hwnd == main_window
-                    || GetParent(data.hwnd) == main_window
-                    || Some(data.hwnd) == sub_window
-                    || sub_window.map_or(false, |sw| GetParent(data.hwnd) == sw);
-                if cond {
-                    self.handler.key_up(data.wParam.0 as u32);
-                    return true;
+                if mmd_window.contains(data.hwnd) {
+                    let vk = data.wParam.0 as u32;
+                    if vk == VK_RETURN.0 as u32 && is_edit(data.hwnd) {
+                        self.handler.edit_committed(data.hwnd);
+                    }
+                    self.handler.key_up(vk, main_window, sub_window);
+                    self.record_dispatched();
+                    return !self.passing.remove(&vk);
+                }
+            }
+            WM_MOUSEWHEEL => unsafe {
+                let mmd_window = self.mmd_window.as_ref().unwrap();
+                if mmd_window.contains(data.hwnd) && data.hwnd == GetFocus() && is_edit(data.hwnd) {
+                    let delta = ((data.wParam.0 >> 16) & 0xffff) as i16 as f64 / WHEEL_DELTA as f64;
+                    let keys = (data.wParam.0 & 0xffff) as u32;
+                    let coarse = (keys & MK_SHIFT) != 0;
+                    let fine = (keys & MK_CONTROL) != 0;
+                    let (main_window, sub_window) = (mmd_window.window, mmd_window.sub_window);
+                    if self
+                        .handler
+                        .scrub(data.hwnd, delta, coarse, fine, main_window, sub_window)
+                    {
+                        data.message = WM_NULL;
+                        return true;
+                    }
                 }
             },
+            WM_RBUTTONDOWN => self.handler.popup_clicked(data.hwnd),
             WM_LBUTTONDOWN => unsafe {
                 if self.settings.kill_focus_with_click {
                     let main_window = self.mmd_window.as_ref().unwrap().window;
                     let focus = GetFocus();
-                    if GetParent(focus) == main_window && get_class_name(focus).to_ascii_uppercase() == "EDIT" {
-                        SetFocus(main_window);
+                    if GetParent(focus) == main_window && is_edit(focus) {
+                        self.handler.kill_focus(main_window);
                         log::debug!("button down and kill focus");
                     }
                 }
@@ -328,7 +665,12 @@ impl Context {
                         }
                         m
                     });
-                    self.handler = Handler::new(&self.mmd_map, key_map);
+                    let macros = Macros::from_file(self.module_path.join(MACROS_PATH)).unwrap_or_default();
+                    let launchers = Launchers::from_file(self.module_path.join(LAUNCHERS_PATH)).unwrap_or_default();
+                    let bookmarks = Bookmarks::from_file(self.module_path.join(BOOKMARKS_PATH));
+                    let snapshots = Snapshots::from_file(self.module_path.join(SNAPSHOTS_PATH));
+                    self.handler = Handler::new(&self.mmd_map, &macros, &launchers, bookmarks, snapshots, key_map);
+                    self.handler.set_kill_focus_mode(self.settings.kill_focus_mode);
                 }
             }
             _ => {}
@@ -336,7 +678,96 @@ impl Context {
         false
     }
 
+    /// Passes the actions dispatched by `Handler` to the recorder, or discards them if not recording.
+    fn record_dispatched(&mut self) {
+        let ids = self.handler.take_dispatched();
+        if let Some(recorder) = self.recorder.as_mut() {
+            ids.into_iter().for_each(|id| recorder.push(id));
+        }
+    }
+
+    /// Stops recording and appends the recorded steps to macros.json as a new macro.
+    fn finish_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            let steps = recorder.finish();
+            if !steps.is_empty() {
+                let path = self.module_path.join(MACROS_PATH);
+                let mut macros = Macros::from_file(&path).unwrap_or_default();
+                let id = macros.push_recorded(steps);
+                if let Err(e) = macros.to_file(&path) {
+                    log::error!("RecordMacro: {:?}", e);
+                }
+                log::debug!("recorded {}", id);
+            }
+        }
+    }
+
+    /// Replaces an expression typed into an edit control with its value before MMD reads the text.
+    ///
+    /// The evaluated value becomes the current value of the next expression typed into the same control.
+    fn evaluate_edit(&mut self, hwnd: HWND) {
+        let text = get_window_text(hwnd);
+        let parse = |text: &str| text.trim().parse::<f64>().ok();
+        let current = self.handler.edit_text(hwnd).and_then(parse).unwrap_or(0.0);
+        let frame = self
+            .mmd_map
+            .get("CurrentFrameFocus")
+            .and_then(|item| match item.kind {
+                ItemKind::Edit(id) => unsafe { Some(GetDlgItem(self.mmd_window.as_ref()?.window, id as _)) },
+                _ => None,
+            })
+            .and_then(|frame_edit| {
+                if frame_edit == hwnd {
+                    Some(current)
+                } else {
+                    parse(&get_window_text(frame_edit))
+                }
+            })
+            .unwrap_or(0.0);
+        if let Some(v) = expr::evaluate(&text, current, &[("x", current), ("frame", frame)]) {
+            set_window_text(hwnd, expr::format_value(v));
+            self.handler.edit_committed(hwnd);
+            log::debug!("evaluate: {} = {}", text, v);
+        }
+    }
+
+    /// Returns whether the key is delivered to MMD and other plugins without being handled.
+    fn is_passed(&mut self, vk: u32) -> bool {
+        if self.settings.consume_keys.contains(&vk) {
+            false
+        } else if self.settings.pass_keys.contains(&vk) {
+            true
+        } else {
+            self.settings.pass_unbound_keys && !self.handler.is_bound(vk)
+        }
+    }
+
+    /// Selects the item of a popup menu opened by `ItemKind::Popup` instead of showing it.
+    ///
+    /// Returns `None` to show the menu as usual.
+    pub fn track_popup_menu(&mut self, menu: HMENU, flags: u32, hwnd: HWND) -> Option<BOOL> {
+        let caption = self.handler.take_popup_caption(hwnd)?;
+        let id = match find_menu_item(menu, caption) {
+            Some(id) => id,
+            None => {
+                log::debug!("Popup: not found {}", caption);
+                return None;
+            }
+        };
+        log::debug!("Popup: {} 0x{:x}", caption, id);
+        if flags & TPM_RETURNCMD.0 != 0 {
+            return Some(BOOL(id as _));
+        }
+        unsafe {
+            PostMessageW(hwnd, WM_COMMAND, WPARAM(id as _), LPARAM(0));
+        }
+        Some(true.into())
+    }
+
     pub fn get_key_state(&self, vk: u32) -> Option<u16> {
+        if self.passing.contains(&vk) {
+            return None;
+        }
         if vk >= 0x07 {
             if self.handler.is_pressed(vk) {
                 Some(0xff80)
@@ -353,6 +784,7 @@ impl Drop for Context {
     fn drop(&mut self) {
         self.settings.raise_timer_resolution = self.time_period.is_some();
         self.settings.to_file(&self.module_path);
+        self.finish_recording();
         log::debug!("drop Context");
     }
 }
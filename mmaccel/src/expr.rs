@@ -0,0 +1,252 @@
+//! Evaluates arithmetic expressions typed into numeric fields.
+//!
+//! * `=expr` is replaced with the value of `expr`.
+//! * `+=expr`, `-=expr`, `*=expr` and `/=expr` apply `expr` to the current value.
+//! * Any other text that is not a plain number is evaluated as `expr`.
+//!
+//! `expr` supports `+ - * / % ^`, parentheses, numbers, variables and the functions
+//! `abs`, `sqrt`, `round`, `floor`, `ceil`, `min` and `max`.
+
+/// The maximum nesting of parentheses, signs and powers. Deeper expressions are invalid.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    variables: &'a [(&'a str, f64)],
+}
+
+impl<'a> Parser<'a> {
+    fn skip_spaces(&mut self) {
+        while self.chars.get(self.pos).map_or(false, |c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut v = self.term()?;
+        loop {
+            if self.eat('+') {
+                v += self.term()?;
+            } else if self.eat('-') {
+                v -= self.term()?;
+            } else {
+                return Some(v);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut v = self.factor()?;
+        loop {
+            if self.eat('*') {
+                v *= self.factor()?;
+            } else if self.eat('/') {
+                v /= self.factor()?;
+            } else if self.eat('%') {
+                v %= self.factor()?;
+            } else {
+                return Some(v);
+            }
+        }
+    }
+
+    /// Every nesting goes through `factor`, so the depth is limited here.
+    fn factor(&mut self) -> Option<f64> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let v = self.unary();
+        self.depth -= 1;
+        v
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        if self.eat('-') {
+            return Some(-self.factor()?);
+        }
+        if self.eat('+') {
+            return self.factor();
+        }
+        let v = self.primary()?;
+        if self.eat('^') {
+            return Some(v.powf(self.factor()?));
+        }
+        Some(v)
+    }
+
+    fn primary(&mut self) -> Option<f64> {
+        let c = self.peek()?;
+        if self.eat('(') {
+            let v = self.expr()?;
+            return if self.eat(')') { Some(v) } else { None };
+        }
+        let begin = self.pos;
+        if c.is_ascii_digit() || c == '.' {
+            while self
+                .chars
+                .get(self.pos)
+                .map_or(false, |c| c.is_ascii_digit() || *c == '.')
+            {
+                self.pos += 1;
+            }
+            return self.chars[begin..self.pos].iter().collect::<String>().parse().ok();
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            while self
+                .chars
+                .get(self.pos)
+                .map_or(false, |c| c.is_ascii_alphanumeric() || *c == '_')
+            {
+                self.pos += 1;
+            }
+            let name = self.chars[begin..self.pos].iter().collect::<String>();
+            if self.eat('(') {
+                let mut args = vec![self.expr()?];
+                while self.eat(',') {
+                    args.push(self.expr()?);
+                }
+                if !self.eat(')') {
+                    return None;
+                }
+                return call(&name, &args);
+            }
+            if name == "pi" {
+                return Some(std::f64::consts::PI);
+            }
+            return self.variables.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+        }
+        None
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Option<f64> {
+    let v = match (name, args) {
+        ("abs", [a]) => a.abs(),
+        ("sqrt", [a]) => a.sqrt(),
+        ("round", [a]) => a.round(),
+        ("floor", [a]) => a.floor(),
+        ("ceil", [a]) => a.ceil(),
+        ("min", [a, rest @ ..]) => rest.iter().fold(*a, |a, b| a.min(*b)),
+        ("max", [a, rest @ ..]) => rest.iter().fold(*a, |a, b| a.max(*b)),
+        _ => return None,
+    };
+    Some(v)
+}
+
+/// Converts full-width ASCII characters typed with an IME to ASCII.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap(),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+fn parse(text: &str, variables: &[(&str, f64)]) -> Option<f64> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        depth: 0,
+        variables,
+    };
+    let v = parser.expr()?;
+    if parser.peek().is_none() && v.is_finite() {
+        Some(v)
+    } else {
+        None
+    }
+}
+
+/// Returns `None` if `text` is a plain number or not a valid expression.
+pub fn evaluate(text: &str, current: f64, variables: &[(&str, f64)]) -> Option<f64> {
+    let text = normalize(text);
+    let text = text.trim();
+    if text.parse::<f64>().is_ok() {
+        return None;
+    }
+    if let Some(expr) = text.strip_prefix('=') {
+        return parse(expr, variables);
+    }
+    let (op, expr) = text.split_at(text.char_indices().nth(1).map_or(text.len(), |(i, _)| i));
+    if let Some(expr) = expr.strip_prefix('=') {
+        let rhs = parse(expr, variables)?;
+        let v = match op {
+            "+" => current + rhs,
+            "-" => current - rhs,
+            "*" => current * rhs,
+            "/" => current / rhs,
+            _ => return None,
+        };
+        return if v.is_finite() { Some(v) } else { None };
+    }
+    parse(text, variables)
+}
+
+/// Formats a value without trailing zeros.
+pub fn format_value(v: f64) -> String {
+    let s = format!("{:.6}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_expr() {
+        let vars = [("x", 10.0), ("frame", 100.0)];
+        assert!(evaluate("=12.5*2", 10.0, &vars) == Some(25.0));
+        assert!(evaluate("+=15", 10.0, &vars) == Some(25.0));
+        assert!(evaluate("/=4", 10.0, &vars) == Some(2.5));
+        assert!(evaluate("frame+30", 10.0, &vars) == Some(130.0));
+        assert!(evaluate("-(1+2)*2^2", 10.0, &vars) == Some(-12.0));
+        assert!(evaluate("max(x, 3, 20) - min(1, 2)", 10.0, &vars) == Some(19.0));
+        assert!(evaluate("＝１＋２", 10.0, &vars) == Some(3.0));
+    }
+
+    #[test]
+    fn evaluate_invalid() {
+        let vars = [("x", 10.0)];
+        assert!(evaluate("12.5", 10.0, &vars).is_none());
+        assert!(evaluate("-3", 10.0, &vars).is_none());
+        assert!(evaluate("=1+", 10.0, &vars).is_none());
+        assert!(evaluate("y*2", 10.0, &vars).is_none());
+        assert!(evaluate("=1/0", 10.0, &vars).is_none());
+        let nested = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(evaluate(&nested, 10.0, &vars).is_none());
+        assert!(evaluate(&"-".repeat(100_000), 10.0, &vars).is_none());
+        let nested = format!("{}1{}", "(".repeat(10), ")".repeat(10));
+        assert!(evaluate(&nested, 10.0, &vars) == Some(1.0));
+    }
+
+    #[test]
+    fn format() {
+        assert!(format_value(25.0) == "25");
+        assert!(format_value(2.5) == "2.5");
+        assert!(format_value(-0.0000001) == "0");
+    }
+}
@@ -1,20 +1,577 @@
+use crate::bookmarks::*;
+use crate::expr::format_value;
+use crate::launchers::{Launcher, Launchers};
+use crate::macros::Macros;
 use crate::mmd_map::*;
+use crate::snapshots::Snapshots;
 use crate::*;
 use key_map::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use windows::Win32::Graphics::Gdi::{ClientToScreen, GetDC, GetDeviceCaps, ReleaseDC, ScreenToClient, LOGPIXELSX};
+
+pub const MACRO_TIMER_ID: usize = 0x4d4d41;
+
+/// The category of mmd_map.json with the controls of the main screen, which are laid out just below the 3D view.
+const VIEW_CONTROLS_CATEGORY: &str = "メイン画面";
+
+/// Marks keyboard messages posted by MMAccel itself in the reserved bits of lParam.
+pub const SYNTHESIZED_KEY: isize = 1 << 25;
+
+fn get_control(mmd_window: HWND, sub_window: Option<HWND>, id: u32) -> HWND {
+    unsafe {
+        let hwnd = GetDlgItem(mmd_window, id as _);
+        hwnd.is_invalid()
+            .then(|| sub_window.map(|sw| GetDlgItem(sw, id as _)))
+            .flatten()
+            .unwrap_or(hwnd)
+    }
+}
+
+/// Returns the checked state of a checkbox-like button or a menu item.
+fn is_checked(item: &ItemKind, mmd_window: HWND, sub_window: Option<HWND>) -> Option<bool> {
+    unsafe {
+        match item {
+            ItemKind::Button(id) => {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if hwnd.is_invalid() {
+                    return None;
+                }
+                Some(SendMessageW(hwnd, BM_GETCHECK, WPARAM(0), LPARAM(0)).0 as u32 == BST_CHECKED.0)
+            }
+            ItemKind::Menu(index, sub_index) => {
+                let m = GetSubMenu(GetMenu(mmd_window), *index as _);
+                Some((GetMenuState(m, *sub_index as _, MF_BYPOSITION) & MF_CHECKED.0) != 0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the 3D view in `client`, which is above the controls of the main screen and right of their left edge.
+///
+/// `controls` is the bounding rectangle of the controls, the whole client area is used without them.
+fn view_area(client: &RECT, controls: Option<&RECT>) -> RECT {
+    match controls {
+        Some(rc) => RECT {
+            left: rc.left.clamp(client.left, client.right),
+            top: client.top,
+            right: client.right,
+            bottom: rc.top.clamp(client.top, client.bottom),
+        },
+        None => *client,
+    }
+}
+
+/// Returns the rectangle of the 3D view in the client coordinates of the main window of MMD.
+fn view_rect(mmd_window: HWND, view_controls: &[u32]) -> RECT {
+    unsafe {
+        let mut client = RECT::default();
+        GetClientRect(mmd_window, &mut client);
+        let controls = view_controls
+            .iter()
+            .map(|id| GetDlgItem(mmd_window, *id as _))
+            .filter(|hwnd| IsWindowVisible(*hwnd).as_bool())
+            .map(|hwnd| {
+                let mut rc = RECT::default();
+                GetWindowRect(hwnd, &mut rc);
+                let mut lt = POINT { x: rc.left, y: rc.top };
+                let mut rb = POINT {
+                    x: rc.right,
+                    y: rc.bottom,
+                };
+                ScreenToClient(mmd_window, &mut lt);
+                ScreenToClient(mmd_window, &mut rb);
+                RECT {
+                    left: lt.x,
+                    top: lt.y,
+                    right: rb.x,
+                    bottom: rb.y,
+                }
+            })
+            .reduce(|a, b| RECT {
+                left: a.left.min(b.left),
+                top: a.top.min(b.top),
+                right: a.right.max(b.right),
+                bottom: a.bottom.max(b.bottom),
+            });
+        view_area(&client, controls.as_ref())
+    }
+}
+
+/// Returns the point in `view` where synthetic mouse operations on the main window of MMD start.
+///
+/// The cursor position is used if it is in `view`, otherwise the center of `view`.
+fn mouse_origin(hwnd: HWND, view: &RECT) -> POINT {
+    unsafe {
+        let mut pt = POINT::default();
+        GetCursorPos(&mut pt);
+        ScreenToClient(hwnd, &mut pt);
+        if (view.left..view.right).contains(&pt.x) && (view.top..view.bottom).contains(&pt.y) {
+            pt
+        } else {
+            POINT {
+                x: (view.left + view.right) / 2,
+                y: (view.top + view.bottom) / 2,
+            }
+        }
+    }
+}
+
+#[inline]
+fn point_to_lparam(pt: POINT) -> LPARAM {
+    LPARAM(((pt.x as u16 as u32) | ((pt.y as u16 as u32) << 16)) as _)
+}
+
+/// Converts a position at 96dpi to the client coordinates of `hwnd`.
+///
+/// Negative positions are relative to the right or bottom edge.
+fn client_point(hwnd: HWND, x: i32, y: i32) -> POINT {
+    unsafe {
+        let dc = GetDC(hwnd);
+        let dpi = GetDeviceCaps(dc, LOGPIXELSX);
+        ReleaseDC(hwnd, dc);
+        let mut rc = RECT::default();
+        GetClientRect(hwnd, &mut rc);
+        let scale = |v: i32, size: i32| {
+            let v = v * dpi / 96;
+            if v < 0 {
+                size + v
+            } else {
+                v
+            }
+        };
+        POINT {
+            x: scale(x, rc.right),
+            y: scale(y, rc.bottom),
+        }
+    }
+}
+
+/// Removes the mnemonic prefixes and the shortcut text from the text of a menu item.
+fn menu_caption(text: &str) -> String {
+    let text = text.split('\t').next().unwrap_or_default();
+    let mut caption = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            if let Some(c) = chars.next() {
+                caption.push(c);
+            }
+        } else {
+            caption.push(c);
+        }
+    }
+    caption
+}
+
+/// Finds the command ID of the enabled item whose caption is `caption`, searching submenus too.
+pub fn find_menu_item(menu: HMENU, caption: &str) -> Option<u32> {
+    unsafe {
+        let count = GetMenuItemCount(menu);
+        (0..count.max(0) as u32).find_map(|i| {
+            let sub_menu = GetSubMenu(menu, i as _);
+            if !sub_menu.is_invalid() {
+                return find_menu_item(sub_menu, caption);
+            }
+            let mut buffer = vec![0u16; 256];
+            let len = GetMenuStringW(menu, i, PWSTR(buffer.as_mut_ptr()), buffer.len() as _, MF_BYPOSITION);
+            let text = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+            let state = GetMenuState(menu, i, MF_BYPOSITION);
+            (menu_caption(&text) == caption && (state & (MF_GRAYED.0 | MF_DISABLED.0)) == 0)
+                .then(|| GetMenuItemID(menu, i as _))
+        })
+    }
+}
+
+/// Posts a drag with the right button for `ViewOp::Orbit` or the middle button for `ViewOp::Pan`,
+/// or wheel notches for `ViewOp::Zoom`.
+fn post_view_operation(hwnd: HWND, view: &RECT, op: ViewOp, dx: i32, dy: i32) {
+    unsafe {
+        let begin = mouse_origin(hwnd, view);
+        let (down, up, button) = match op {
+            ViewOp::Orbit => (WM_RBUTTONDOWN, WM_RBUTTONUP, MK_RBUTTON),
+            ViewOp::Pan => (WM_MBUTTONDOWN, WM_MBUTTONUP, MK_MBUTTON),
+            ViewOp::Zoom => {
+                let mut pt = begin;
+                ClientToScreen(hwnd, &mut pt);
+                let delta = dx
+                    .saturating_mul(WHEEL_DELTA as i32)
+                    .clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16 as u32;
+                PostMessageW(hwnd, WM_MOUSEWHEEL, WPARAM((delta << 16) as _), point_to_lparam(pt));
+                return;
+            }
+        };
+        let end = POINT {
+            x: begin.x.saturating_add(dx),
+            y: begin.y.saturating_add(dy),
+        };
+        let button = WPARAM(button as _);
+        PostMessageW(hwnd, down, button, point_to_lparam(begin));
+        PostMessageW(hwnd, WM_MOUSEMOVE, button, point_to_lparam(end));
+        PostMessageW(hwnd, up, WPARAM(0), point_to_lparam(end));
+    }
+}
+
+/// Commits the text of an edit control by posting Enter to it.
+fn commit_edit(hwnd: HWND) {
+    unsafe {
+        PostMessageW(hwnd, WM_KEYDOWN, WPARAM(VK_RETURN.0 as _), LPARAM(SYNTHESIZED_KEY | 1));
+        PostMessageW(hwnd, WM_CHAR, WPARAM(b'\r' as _), LPARAM(SYNTHESIZED_KEY | 1));
+        PostMessageW(
+            hwnd,
+            WM_KEYUP,
+            WPARAM(VK_RETURN.0 as _),
+            LPARAM(SYNTHESIZED_KEY | (3 << 30) | 1),
+        );
+    }
+}
+
+/// Returns `None` if the text of the frame edit control is not a frame number, e.g. while it is being typed.
+#[inline]
+fn current_frame(hwnd: HWND) -> Option<i32> {
+    get_window_text(hwnd).trim().parse::<i32>().ok()
+}
+
+#[inline]
+fn jump_to_frame(hwnd: HWND, frame: i32) {
+    set_window_text(hwnd, frame.max(0).to_string());
+    commit_edit(hwnd);
+}
+
+/// Returns whether `hwnd` accepts text input, including edit controls of other classes such as RichEdit.
+pub fn is_edit(hwnd: HWND) -> bool {
+    let class_name = get_class_name(hwnd).to_ascii_uppercase();
+    if class_name == "EDIT" || class_name.starts_with("RICHEDIT") {
+        return true;
+    }
+    unsafe { (SendMessageW(hwnd, WM_GETDLGCODE, WPARAM(0), LPARAM(0)).0 as u32 & DLGC_HASSETSEL) != 0 }
+}
+
+fn is_available(item: &ItemKind, mmd_window: HWND, sub_window: Option<HWND>) -> bool {
+    unsafe {
+        match item {
+            ItemKind::Button(id)
+            | ItemKind::Edit(id)
+            | ItemKind::Combo(_, id)
+            | ItemKind::Frame(_, id, _)
+            | ItemKind::Bookmark(_, id)
+            | ItemKind::Step(StepSpec { id, .. })
+            | ItemKind::Preset(id, _)
+            | ItemKind::ButtonCheck(id, _)
+            | ItemKind::Trackbar(_, id)
+            | ItemKind::Message(MessageSpec { id, .. }) => {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool()
+            }
+            ItemKind::Menu(index, sub_index) | ItemKind::MenuCheck(index, sub_index, _) => {
+                let m = GetSubMenu(GetMenu(mmd_window), *index as _);
+                (GetMenuState(m, *sub_index as _, MF_BYPOSITION) & MFS_DISABLED.0) == 0
+            }
+            ItemKind::Fold(hide_id, show_id) => {
+                IsWindowVisible(GetDlgItem(mmd_window, *hide_id as _)).as_bool()
+                    || IsWindowVisible(GetDlgItem(mmd_window, *show_id as _)).as_bool()
+            }
+            _ => true,
+        }
+    }
+}
+
+/// What to do with the text being typed when the focus is taken from an edit control.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillFocusMode {
+    Leave,
+    Commit,
+    Revert,
+}
+
+impl Default for KillFocusMode {
+    fn default() -> Self {
+        Self::Leave
+    }
+}
+
+/// The data of `ItemKind`s that is not `Copy`, indexed by the kinds.
+#[derive(Default)]
+struct Tables {
+    presets: Vec<Vec<f64>>,
+    classes: Vec<ClassTarget>,
+    texts: Vec<String>,
+}
+
+/// A window found by the class name, nearest to the control `anchor` among its siblings if given.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct ClassTarget {
+    class: String,
+    anchor: Option<u32>,
+}
+
+/// Registers the data of `item` and the binding to `tables` and returns the kind indexing them.
+///
+/// Every `TableIndex` of the kind is resolved here except `ComboOp::Select` without `text`.
+fn register_item(item: &Item, values: Option<&Vec<f64>>, text: Option<&String>, tables: &mut Tables) -> ItemKind {
+    let push_text = |text: &String| {
+        tables.texts.push(text.clone());
+        TableIndex::Resolved(tables.texts.len() - 1)
+    };
+    match item.kind {
+        ItemKind::Combo(ComboOp::Select(_), id) => {
+            ItemKind::Combo(ComboOp::Select(text.map_or(TableIndex::Unresolved, push_text)), id)
+        }
+        ItemKind::Popup(target, x, y, _) => {
+            let text = text.map(push_text);
+            ItemKind::Popup(register_target(item, target, tables), x, y, text)
+        }
+        ItemKind::Preset(id, _) => {
+            let values = values.or(item.meta.values.as_ref()).cloned().unwrap_or_default();
+            tables.presets.push(values);
+            ItemKind::Preset(id, TableIndex::Resolved(tables.presets.len() - 1))
+        }
+        ItemKind::Scroll(op, target) => ItemKind::Scroll(op, register_target(item, target, tables)),
+        ItemKind::Click(target, x, y) => ItemKind::Click(register_target(item, target, tables), x, y),
+        kind => kind,
+    }
+}
+
+fn register_target(item: &Item, target: WindowTarget, tables: &mut Tables) -> WindowTarget {
+    if let WindowTarget::Id(_) = target {
+        return target;
+    }
+    let class = ClassTarget {
+        class: item.class.clone().unwrap_or_default(),
+        anchor: item
+            .meta
+            .anchor
+            .as_ref()
+            .and_then(|id| u32::from_str_radix(id, 16).ok()),
+    };
+    let index = match tables.classes.iter().position(|c| *c == class) {
+        Some(index) => index,
+        None => {
+            tables.classes.push(class);
+            tables.classes.len() - 1
+        }
+    };
+    WindowTarget::Class(TableIndex::Resolved(index))
+}
+
+/// Returns the squared distance between the nearest edges of two rectangles, 0 if they overlap.
+fn rect_distance(a: &RECT, b: &RECT) -> i64 {
+    let dx = (a.left - b.right).max(b.left - a.right).max(0) as i64;
+    let dy = (a.top - b.bottom).max(b.top - a.bottom).max(0) as i64;
+    dx * dx + dy * dy
+}
+
+/// Returns the candidate nearest to `anchor`, the first one of the same distance.
+fn nearest<T>(anchor: &RECT, candidates: impl IntoIterator<Item = (T, RECT)>) -> Option<T> {
+    candidates
+        .into_iter()
+        .enumerate()
+        .min_by_key(|(i, (_, rc))| (rect_distance(anchor, rc), *i))
+        .map(|(_, (v, _))| v)
+}
+
+/// Finds a visible child window of MMD. Windows found by the class name are also filtered by `accept`.
+///
+/// With `ClassTarget::anchor` the target is the window of the class nearest to the anchor control
+/// among the windows with the same parent, otherwise the first window of the class.
+fn find_window(
+    mmd_window: HWND,
+    sub_window: Option<HWND>,
+    target: WindowTarget,
+    classes: &[ClassTarget],
+    accept: impl Fn(HWND) -> bool,
+) -> Option<HWND> {
+    unsafe {
+        match target {
+            WindowTarget::Id(id) => {
+                Some(get_control(mmd_window, sub_window, id)).filter(|hwnd| IsWindowVisible(*hwnd).as_bool())
+            }
+            WindowTarget::Class(TableIndex::Unresolved) => None,
+            WindowTarget::Class(TableIndex::Resolved(index)) => {
+                let target = classes.get(index)?;
+                let is_target = |hwnd: &HWND| {
+                    get_class_name(*hwnd).eq_ignore_ascii_case(&target.class)
+                        && IsWindowVisible(*hwnd).as_bool()
+                        && accept(*hwnd)
+                };
+                match target.anchor {
+                    Some(id) => {
+                        let anchor = get_control(mmd_window, sub_window, id);
+                        if anchor.is_invalid() || !IsWindow(anchor).as_bool() {
+                            return None;
+                        }
+                        let parent = GetParent(anchor);
+                        let mut anchor_rc = RECT::default();
+                        GetWindowRect(anchor, &mut anchor_rc);
+                        let candidates = enum_child_windows(parent)
+                            .into_iter()
+                            .filter(|hwnd| GetParent(*hwnd) == parent && is_target(hwnd))
+                            .map(|hwnd| {
+                                let mut rc = RECT::default();
+                                GetWindowRect(hwnd, &mut rc);
+                                (hwnd, rc)
+                            });
+                        nearest(&anchor_rc, candidates)
+                    }
+                    // The MMD window itself is a target too, for the timeline and the frame list drawn on it.
+                    None => std::iter::once(mmd_window)
+                        .filter(is_target)
+                        .chain(
+                            std::iter::once(mmd_window)
+                                .chain(sub_window)
+                                .flat_map(enum_child_windows)
+                                .filter(is_target),
+                        )
+                        .next(),
+                }
+            }
+        }
+    }
+}
+
+/// The edit controls of a panel in the order of `meta.order`.
+///
+/// `register` is the button registering their values, whose category is `label`.
+struct Panel {
+    label: String,
+    register: Option<u32>,
+    fields: Vec<u32>,
+}
+
+/// Returns the first available field after `current` in `dir`, wrapping around `fields`.
+fn cycle_field(fields: &[u32], current: u32, dir: ComboDir, available: impl Fn(u32) -> bool) -> Option<u32> {
+    let pos = fields.iter().position(|id| *id == current)?;
+    let len = fields.len();
+    (1..len)
+        .map(|i| match dir {
+            ComboDir::Next => fields[(pos + i) % len],
+            ComboDir::Prev => fields[(pos + len - i) % len],
+        })
+        .find(|id| available(*id))
+}
+
+/// Returns whether a dispatched action is recorded by its ID.
+///
+/// The WM_COMMAND caused by the action is not recorded again, see `Handler::take_caused_command`.
+fn is_recorded(kind: &ItemKind) -> bool {
+    match kind {
+        ItemKind::Key(k) => *k != VK_CONTROL.0 as u32 && *k != VK_SHIFT.0 as u32,
+        ItemKind::Macro(_) | ItemKind::Launch(_) => false,
+        _ => true,
+    }
+}
+
+/// How long a command caused by MMAccel is waited for to arrive at the hooks.
+const CAUSED_COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Clicks a button and remembers the command it causes in `caused`.
+unsafe fn post_click(hwnd: HWND, caused: &mut Vec<(u32, Instant)>) {
+    PostMessageW(hwnd, BM_CLICK, WPARAM(0), LPARAM(0));
+    caused.push((GetDlgCtrlID(hwnd) as u32, Instant::now()));
+}
+
+#[derive(Clone, Debug)]
+struct MacroStep {
+    id: String,
+    kind: ItemKind,
+    delay: u32,
+    wait: Option<u32>,
+}
+
+/// A popup menu requested by `ItemKind::Popup`, armed when the right click posted to `target` is retrieved.
+struct PendingPopup {
+    target: HWND,
+    text: usize,
+    armed: bool,
+    at: Instant,
+}
+
+struct Runner {
+    macro_index: usize,
+    index: usize,
+    next_at: Instant,
+    deadline: Option<Instant>,
+    pressed: Option<u32>,
+}
+
+/// The actions with their IDs.
+struct MomentaryAction {
+    release: Option<(String, ItemKind)>,
+    restore: Vec<(String, ItemKind)>,
+}
+
+struct Action {
+    id: String,
+    kind: ItemKind,
+    repeat: Option<Repeat>,
+    momentary: Option<MomentaryAction>,
+    multiplier: Option<Multiplier>,
+    /// Whether the binding has options changing the action, which recorded macro steps cannot keep.
+    parameterized: bool,
+    in_edit: bool,
+}
+
+struct ActiveMomentary {
+    keys: Keys,
+    release: Option<(String, ItemKind)>,
+    /// The IDs of `release` and `restore`, shared by momentary actions switching the same state.
+    group: Vec<String>,
+}
+
+impl ActiveMomentary {
+    #[inline]
+    fn same_group(&self, other: &Self) -> bool {
+        self.group.iter().any(|id| other.group.contains(id))
+    }
+}
+
+struct Held {
+    keys: Keys,
+    pressed_at: Instant,
+    fired_at: Instant,
+}
 
 pub struct Handler {
     input: Vec<u8>,
     input_keys: Keys,
-    handler: HashMap<Keys, ItemKind>,
+    handler: HashMap<Keys, Action>,
+    held: Option<Held>,
+    macros: Vec<Vec<MacroStep>>,
+    launchers: Vec<Launcher>,
+    waiting_launchers: HashSet<usize>,
+    runner: Option<Runner>,
+    momentaries: Vec<ActiveMomentary>,
+    bookmarks: Bookmarks,
+    snapshots: Snapshots,
+    panels: Vec<Panel>,
+    kill_focus_mode: KillFocusMode,
+    edit_text: Option<(HWND, String)>,
+    pending_focus: Option<(HWND, HWND)>,
+    synthesized: Option<u32>,
+    key_combo: Option<KeyComboSpec>,
     key_states: HashMap<u32, bool>,
     folds: Vec<u32>,
     unfolds: Vec<u32>,
+    view_controls: Vec<u32>,
+    scrubs: HashMap<u32, StepSpec>,
+    tables: Tables,
     tabstop: bool,
+    pending_popup: Option<PendingPopup>,
+    dispatched: Vec<String>,
+    caused_commands: Vec<(u32, Instant)>,
 }
 
 impl Handler {
-    pub fn new(mmd_map: &MmdMap, key_map: KeyMap) -> Self {
+    pub fn new(
+        mmd_map: &MmdMap,
+        macros: &Macros,
+        launchers: &Launchers,
+        bookmarks: Bookmarks,
+        snapshots: Snapshots,
+        key_map: KeyMap,
+    ) -> Self {
         let mut key_states = HashMap::new();
         mmd_map
             .iter()
@@ -32,208 +589,906 @@ impl Handler {
                 folds.push(hide);
                 unfolds.push(show);
             });
+        let view_controls = mmd_map
+            .iter()
+            .filter(|(_, item)| item.category == VIEW_CONTROLS_CATEGORY)
+            .filter_map(|(_, item)| match item.kind {
+                ItemKind::Button(id) | ItemKind::Edit(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+        let scrubs = mmd_map
+            .iter()
+            .filter_map(|(_, item)| match (item.kind, item.meta.step) {
+                (ItemKind::Edit(id), Some(step)) => Some((
+                    id,
+                    StepSpec {
+                        id,
+                        step,
+                        min: item.meta.min.unwrap_or(f64::MIN),
+                        max: item.meta.max.unwrap_or(f64::MAX),
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        let mut fields = BTreeMap::new();
+        for (_, item) in mmd_map.iter() {
+            if let (ItemKind::Edit(id), Some(panel)) = (item.kind, item.meta.panel.as_ref()) {
+                fields
+                    .entry(panel.as_str())
+                    .or_insert_with(Vec::new)
+                    .push((item.meta.order.unwrap_or(0), id));
+            }
+        }
+        let panels = fields
+            .into_iter()
+            .map(|(name, mut fields)| {
+                fields.sort_unstable();
+                let register = mmd_map.iter().find_map(|(_, item)| match item.kind {
+                    ItemKind::Button(id) if item.meta.panel.as_deref() == Some(name) => Some((id, item)),
+                    _ => None,
+                });
+                Panel {
+                    label: register.map(|(_, item)| item.category.clone()).unwrap_or_default(),
+                    register: register.map(|(id, _)| id),
+                    fields: fields.into_iter().map(|(_, id)| id).collect(),
+                }
+            })
+            .collect();
         let mut handler = HashMap::new();
+        let mut macro_steps = vec![];
+        let mut launcher_table = vec![];
+        let mut tables = Tables::default();
+        let resolve = |id: &String| {
+            let item = mmd_map.get(id);
+            if item.is_none() {
+                log::error!("unknown action: {}", id);
+            }
+            item
+        };
         for (k, v) in key_map.into_iter() {
             if let Some(item) = mmd_map.get(&k) {
-                handler.insert(v, item.kind);
+                let momentary = v.momentary.as_ref().map(|m| MomentaryAction {
+                    release: m
+                        .release
+                        .as_ref()
+                        .and_then(|id| Some((id.clone(), register_item(resolve(id)?, None, None, &mut tables)))),
+                    restore: m
+                        .restore
+                        .iter()
+                        .filter_map(|id| Some((id.clone(), register_item(resolve(id)?, None, None, &mut tables))))
+                        .collect(),
+                });
+                let repeat = v.repeat.or_else(|| {
+                    if momentary.is_some() {
+                        Some(Repeat::disabled())
+                    } else {
+                        item.meta.repeat.and_then(|r| (!r).then(Repeat::disabled))
+                    }
+                });
+                let kind = match (
+                    register_item(item, v.values.as_ref(), v.text.as_ref(), &mut tables),
+                    v.frames,
+                    v.step,
+                    v.register,
+                    v.wrap,
+                ) {
+                    (ItemKind::Frame(jump, id, _), Some(frames), ..) => ItemKind::Frame(jump, id, frames),
+                    (ItemKind::Step(spec), _, Some(step), ..) => ItemKind::Step(StepSpec { step, ..spec }),
+                    (ItemKind::View(op, dx, dy), _, Some(step), ..) => {
+                        let step = step as i32;
+                        ItemKind::View(op, dx.signum() * step, dy.signum() * step)
+                    }
+                    (ItemKind::Trackbar(TrackbarOp::Step(_), id), _, Some(step), ..) => {
+                        ItemKind::Trackbar(TrackbarOp::Step(step as _), id)
+                    }
+                    (ItemKind::Snapshot(SnapshotOp::Load, panel, n), _, _, Some(true), _) => {
+                        ItemKind::Snapshot(SnapshotOp::LoadAndRegister, panel, n)
+                    }
+                    (ItemKind::Combo(ComboOp::Prev, id), .., Some(true)) => ItemKind::Combo(ComboOp::PrevWrap, id),
+                    (ItemKind::Combo(ComboOp::Next, id), .., Some(true)) => ItemKind::Combo(ComboOp::NextWrap, id),
+                    (kind, ..) => kind,
+                };
+                handler.insert(
+                    v.keys,
+                    Action {
+                        id: k,
+                        kind,
+                        repeat,
+                        momentary,
+                        multiplier: v.multiplier,
+                        parameterized: v.frames.is_some()
+                            || v.step.is_some()
+                            || v.values.is_some()
+                            || v.register.is_some()
+                            || v.wrap.is_some()
+                            || v.text.is_some(),
+                        in_edit: v.in_edit.or(item.meta.in_edit).unwrap_or(false),
+                    },
+                );
+            } else if let Some(m) = macros.get(&k) {
+                let steps = m
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        mmd_map.get(&step.action).map(|item| MacroStep {
+                            id: step.action.clone(),
+                            kind: register_item(item, None, None, &mut tables),
+                            delay: step.delay,
+                            wait: step.wait,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(steps) = steps {
+                    handler.insert(
+                        v.keys,
+                        Action {
+                            id: k,
+                            kind: ItemKind::Macro(macro_steps.len()),
+                            repeat: v.repeat.or_else(|| Some(Repeat::disabled())),
+                            momentary: None,
+                            multiplier: None,
+                            parameterized: false,
+                            in_edit: v.in_edit.unwrap_or(false),
+                        },
+                    );
+                    macro_steps.push(steps);
+                } else {
+                    log::error!("macro error: {}", k);
+                }
+            } else if let Some(l) = launchers.get(&k) {
+                handler.insert(
+                    v.keys,
+                    Action {
+                        id: k,
+                        kind: ItemKind::Launch(launcher_table.len()),
+                        repeat: v.repeat.or_else(|| Some(Repeat::disabled())),
+                        momentary: None,
+                        multiplier: None,
+                        parameterized: false,
+                        in_edit: v.in_edit.unwrap_or(false),
+                    },
+                );
+                launcher_table.push(l.clone());
             } else {
                 log::error!("handler.insert error: {}", k);
             }
         }
-        handler.insert(Keys::from_slice(&[VK_CONTROL.0 as _]), ItemKind::Key(VK_CONTROL.0 as _));
-        handler.insert(Keys::from_slice(&[VK_SHIFT.0 as _]), ItemKind::Key(VK_SHIFT.0 as _));
+        handler.insert(
+            Keys::from_slice(&[VK_CONTROL.0 as _]),
+            Action {
+                id: String::new(),
+                kind: ItemKind::Key(VK_CONTROL.0 as _),
+                repeat: None,
+                momentary: None,
+                multiplier: None,
+                parameterized: false,
+                in_edit: false,
+            },
+        );
+        handler.insert(
+            Keys::from_slice(&[VK_SHIFT.0 as _]),
+            Action {
+                id: String::new(),
+                kind: ItemKind::Key(VK_SHIFT.0 as _),
+                repeat: None,
+                momentary: None,
+                multiplier: None,
+                parameterized: false,
+                in_edit: false,
+            },
+        );
         key_states.insert(VK_CONTROL.0 as _, false);
         key_states.insert(VK_SHIFT.0 as _, false);
         Self {
             input: vec![0; 256],
             input_keys: Keys::with_capacity(3),
             handler,
+            held: None,
+            macros: macro_steps,
+            launchers: launcher_table,
+            waiting_launchers: HashSet::new(),
+            runner: None,
+            momentaries: vec![],
+            bookmarks,
+            snapshots,
+            panels,
+            kill_focus_mode: KillFocusMode::default(),
+            edit_text: None,
+            pending_focus: None,
+            synthesized: None,
+            key_combo: None,
             key_states,
             folds,
             unfolds,
+            view_controls,
+            scrubs,
+            tables,
             tabstop: false,
+            pending_popup: None,
+            dispatched: vec![],
+            caused_commands: vec![],
         }
     }
 
-    pub fn key_down(&mut self, vk: u32, mmd_window: HWND, sub_window: Option<HWND>, hwnd: HWND) {
-        fn handle(
-            item: &ItemKind,
-            key_states: &mut HashMap<u32, bool>,
-            folds: &[u32],
-            unfolds: &[u32],
-            mmd_window: HWND,
-            sub_window: Option<HWND>,
-            hwnd: HWND,
-        ) {
-            if get_class_name(hwnd).to_ascii_uppercase() == "EDIT" {
-                unsafe {
-                    if item == &ItemKind::KillFocus {
-                        SetFocus(mmd_window);
-                        log::debug!("KillFocus")
-                    }
+    /// Performs an action. Also used for actions requested from outside of key input such as the MMAccel menu.
+    pub fn handle(&mut self, item: ItemKind, mmd_window: HWND, sub_window: Option<HWND>) {
+        match &item {
+            ItemKind::Key(k) => {
+                if self.key_states.get(k).is_some() && *k != VK_SHIFT.0 as u32 && *k != VK_CONTROL.0 as u32 {
+                    *self.key_states.get_mut(&(VK_SHIFT.0 as u32)).unwrap() = false;
+                    *self.key_states.get_mut(&(VK_CONTROL.0 as u32)).unwrap() = false;
+                }
+                if let Some(ks) = self.key_states.get_mut(k) {
+                    *ks = true;
+                    log::debug!("Key: 0x{:x}", k);
                 }
-                return;
             }
-            match item {
-                ItemKind::Key(k) => {
-                    if key_states.get(k).is_some() && *k != VK_SHIFT.0 as u32 && *k != VK_CONTROL.0 as u32 {
-                        *key_states.get_mut(&(VK_SHIFT.0 as u32)).unwrap() = false;
-                        *key_states.get_mut(&(VK_CONTROL.0 as u32)).unwrap() = false;
+            ItemKind::KeyCombo(spec) => {
+                self.key_combo = Some(*spec);
+                log::debug!("KeyCombo: {:?}", spec);
+            }
+            ItemKind::Button(id) => unsafe {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
+                    post_click(hwnd, &mut self.caused_commands);
+                    log::debug!("Button: 0x{:x}", id);
+                }
+            },
+            ItemKind::Edit(id) => unsafe {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
+                    SetFocus(hwnd);
+                    log::debug!("Edit: 0x{:x}", id);
+                }
+            },
+            ItemKind::Combo(op, id) => unsafe {
+                #[inline]
+                unsafe fn post_set_cur_sel(hwnd: HWND, id: u32, parent: HWND, index: isize) {
+                    PostMessageW(hwnd, CB_SETCURSEL, WPARAM(index as _), LPARAM(0));
+                    PostMessageW(
+                        parent,
+                        WM_COMMAND,
+                        WPARAM(((id & 0xffff) | (CBN_SELCHANGE << 16)) as _),
+                        LPARAM(hwnd.0),
+                    );
+                    log::debug!("Combo: 0x{:x}", id);
+                }
+
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if !IsWindowVisible(hwnd).as_bool() || !IsWindowEnabled(hwnd).as_bool() {
+                    return;
+                }
+                let index = SendMessageA(hwnd, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+                let size = SendMessageA(hwnd, CB_GETCOUNT, WPARAM(0), LPARAM(0)).0;
+                let next = match op {
+                    ComboOp::Prev if index >= 1 => index - 1,
+                    ComboOp::Next if index < size - 1 => index + 1,
+                    ComboOp::PrevWrap if size > 0 => {
+                        if index >= 1 {
+                            index - 1
+                        } else {
+                            size - 1
+                        }
                     }
-                    if let Some(ks) = key_states.get_mut(k) {
-                        *ks = true;
-                        log::debug!("Key: 0x{:x}", k);
+                    ComboOp::NextWrap if size > 0 => (index + 1) % size,
+                    ComboOp::First if size > 0 => 0,
+                    ComboOp::Last if size > 0 => size - 1,
+                    ComboOp::Select(TableIndex::Resolved(text)) => {
+                        let text = to_wchar(&self.tables.texts[*text]);
+                        SendMessageW(hwnd, CB_FINDSTRINGEXACT, WPARAM(usize::MAX), LPARAM(text.as_ptr() as _)).0
                     }
+                    ComboOp::Select(TableIndex::Unresolved) => {
+                        log::debug!("Combo: no text to select 0x{:x}", id);
+                        return;
+                    }
+                    _ => return,
+                };
+                if next >= 0 && next != index {
+                    post_set_cur_sel(hwnd, *id, mmd_window, next);
                 }
-                ItemKind::Button(id) => unsafe {
-                    let hwnd = GetDlgItem(mmd_window, *id as _);
-                    let hwnd = hwnd
-                        .is_invalid()
-                        .then(|| sub_window.map(|sw| GetDlgItem(sw, *id as _)))
-                        .flatten()
-                        .unwrap_or(hwnd);
-                    if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
-                        PostMessageA(hwnd, BM_CLICK, WPARAM(0), LPARAM(0));
-                        log::debug!("Button: 0x{:x}", id);
+            },
+            ItemKind::Menu(index, sub_index) => unsafe {
+                let m = GetSubMenu(GetMenu(mmd_window), *index as _);
+                let state = GetMenuState(m, *sub_index as _, MF_BYPOSITION);
+                if (state & MFS_DISABLED.0) == 0 {
+                    let command = GetMenuItemID(m, *sub_index as _);
+                    PostMessageA(mmd_window, WM_COMMAND, WPARAM(command as _), LPARAM(0));
+                    self.caused_commands.push((command, Instant::now()));
+                    log::debug!("Menu: {}, {}", index, sub_index);
+                }
+            },
+            ItemKind::ButtonCheck(id, checked) => {
+                let button = ItemKind::Button(*id);
+                if is_checked(&button, mmd_window, sub_window) != Some(*checked) {
+                    self.handle(button, mmd_window, sub_window);
+                }
+            }
+            ItemKind::MenuCheck(index, sub_index, checked) => {
+                let menu = ItemKind::Menu(*index, *sub_index);
+                if is_checked(&menu, mmd_window, sub_window) != Some(*checked) {
+                    self.handle(menu, mmd_window, sub_window);
+                }
+            }
+            ItemKind::Fold(hide_id, show_id) => unsafe {
+                let hide = GetDlgItem(mmd_window, *hide_id as _);
+                if IsWindowVisible(hide).as_bool() {
+                    post_click(hide, &mut self.caused_commands);
+                    log::debug!("Fold: 0x{:x}", hide_id);
+                } else {
+                    let show = GetDlgItem(mmd_window, *show_id as _);
+                    post_click(show, &mut self.caused_commands);
+                    log::debug!("Fold: 0x{:x}", show_id);
+                }
+            },
+            ItemKind::Frame(jump, id, n) => {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if !is_available(&item, mmd_window, sub_window) {
+                    return;
+                }
+                let frame = match jump {
+                    FrameJump::Relative => match current_frame(hwnd) {
+                        Some(current) => current.saturating_add(*n),
+                        None => {
+                            log::debug!("Frame: invalid current frame");
+                            return;
+                        }
+                    },
+                    FrameJump::Absolute => *n,
+                };
+                jump_to_frame(hwnd, frame);
+                log::debug!("Frame: {}", frame);
+            }
+            ItemKind::Bookmark(op, id) => {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if !is_available(&item, mmd_window, sub_window) {
+                    return;
+                }
+                let project = current_project(mmd_window);
+                let current = match (op, current_frame(hwnd)) {
+                    (BookmarkOp::Go(_), current) => current.unwrap_or(0),
+                    (_, Some(current)) => current,
+                    (_, None) => {
+                        log::debug!("Bookmark: invalid current frame");
+                        return;
                     }
-                },
-                ItemKind::Edit(id) => unsafe {
-                    let hwnd = GetDlgItem(mmd_window, *id as _);
-                    let hwnd = hwnd
-                        .is_invalid()
-                        .then(|| sub_window.map(|sw| GetDlgItem(sw, *id as _)))
-                        .flatten()
-                        .unwrap_or(hwnd);
-                    if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
-                        SetFocus(hwnd);
-                        log::debug!("Edit: 0x{:x}", id);
+                };
+                let frame = match op {
+                    BookmarkOp::Set(_) if project.is_empty() => {
+                        log::debug!("Bookmark: the project is not saved");
+                        return;
                     }
-                },
-                ItemKind::Combo(dir, id) => unsafe {
-                    #[inline]
-                    unsafe fn post_set_cur_sel(hwnd: HWND, id: u32, parent: HWND, index: isize) {
-                        PostMessageW(hwnd, CB_SETCURSEL, WPARAM(index as _), LPARAM(0));
-                        PostMessageW(
-                            parent,
-                            WM_COMMAND,
-                            WPARAM(((id & 0xffff) | (CBN_SELCHANGE << 16)) as _),
-                            LPARAM(hwnd.0),
-                        );
-                        log::debug!("Combo: 0x{:x}", id);
+                    BookmarkOp::Set(n) => {
+                        self.bookmarks.set(&project, *n, current);
+                        log::debug!("Bookmark: set {} = {}", n, current);
+                        return;
                     }
-
-                    let hwnd = GetDlgItem(mmd_window, *id as _);
-                    let hwnd = hwnd
-                        .is_invalid()
-                        .then(|| sub_window.map(|sw| GetDlgItem(sw, *id as _)))
-                        .flatten()
-                        .unwrap_or(hwnd);
-                    if !IsWindowVisible(hwnd).as_bool() || !IsWindowEnabled(hwnd).as_bool() {
+                    BookmarkOp::Go(n) => self.bookmarks.get(&project, *n).map(|bookmark| bookmark.frame),
+                    BookmarkOp::Next => self.bookmarks.next(&project, current),
+                    BookmarkOp::Prev => self.bookmarks.prev(&project, current),
+                };
+                if let Some(frame) = frame {
+                    jump_to_frame(hwnd, frame);
+                    log::debug!("Bookmark: {}", frame);
+                }
+            }
+            ItemKind::Step(spec) => {
+                let hwnd = get_control(mmd_window, sub_window, spec.id);
+                if !is_available(&item, mmd_window, sub_window) {
+                    return;
+                }
+                let current = get_window_text(hwnd).trim().parse::<f64>().unwrap_or(0.0);
+                let value = (current + spec.step).clamp(spec.min, spec.max);
+                set_window_text(hwnd, format_value(value));
+                commit_edit(hwnd);
+                log::debug!("Step: 0x{:x} = {}", spec.id, value);
+            }
+            ItemKind::Preset(id, index) => {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if !is_available(&item, mmd_window, sub_window) {
+                    return;
+                }
+                let values = match index {
+                    TableIndex::Resolved(index) => &self.tables.presets[*index],
+                    TableIndex::Unresolved => {
+                        log::error!("Preset: unresolved 0x{:x}", id);
                         return;
                     }
-                    let index = SendMessageA(hwnd, CB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
-                    let size = SendMessageA(hwnd, CB_GETCOUNT, WPARAM(0), LPARAM(0)).0;
-                    match dir {
-                        ComboDir::Prev if index >= 1 => post_set_cur_sel(hwnd, *id, mmd_window, index - 1),
-                        ComboDir::Next if index < size - 1 => post_set_cur_sel(hwnd, *id, mmd_window, index + 1),
-                        _ => {}
+                };
+                // Cycles through the values starting from the one after the current value.
+                let current = get_window_text(hwnd).trim().parse::<f64>().ok();
+                let next = current
+                    .and_then(|current| values.iter().position(|v| (v - current).abs() < 1e-6))
+                    .map_or(0, |i| i + 1);
+                if let Some(value) = values.get(next).or_else(|| values.first()) {
+                    set_window_text(hwnd, format_value(*value));
+                    commit_edit(hwnd);
+                    log::debug!("Preset: 0x{:x} = {}", id, value);
+                }
+            }
+            ItemKind::Trackbar(op, id) => unsafe {
+                let hwnd = get_control(mmd_window, sub_window, *id);
+                if !IsWindowVisible(hwnd).as_bool() || !IsWindowEnabled(hwnd).as_bool() {
+                    return;
+                }
+                let pos = SendMessageW(hwnd, TBM_GETPOS, WPARAM(0), LPARAM(0)).0 as i32;
+                let min = SendMessageW(hwnd, TBM_GETRANGEMIN, WPARAM(0), LPARAM(0)).0 as i32;
+                let max = SendMessageW(hwnd, TBM_GETRANGEMAX, WPARAM(0), LPARAM(0)).0 as i32;
+                let next = match op {
+                    TrackbarOp::Step(step) => pos.saturating_add(*step),
+                    TrackbarOp::Set(v) => *v,
+                }
+                .clamp(min, max);
+                if next == pos {
+                    return;
+                }
+                // The parent updates its value from the notification as if the thumb were dragged.
+                let msg = if (GetWindowLongW(hwnd, GWL_STYLE) as u32 & TBS_VERT) != 0 {
+                    WM_VSCROLL
+                } else {
+                    WM_HSCROLL
+                };
+                let parent = GetParent(hwnd);
+                PostMessageW(hwnd, TBM_SETPOS, WPARAM(1), LPARAM(next as _));
+                PostMessageW(
+                    parent,
+                    msg,
+                    WPARAM((TB_THUMBPOSITION | ((next as u32 & 0xffff) << 16)) as _),
+                    LPARAM(hwnd.0),
+                );
+                PostMessageW(parent, msg, WPARAM(TB_ENDTRACK as _), LPARAM(hwnd.0));
+                log::debug!("Trackbar: 0x{:x} {}", id, next);
+            },
+            ItemKind::Message(spec) => unsafe {
+                let hwnd = get_control(mmd_window, sub_window, spec.id);
+                if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
+                    let (wparam, lparam) = (WPARAM(spec.wparam), LPARAM(spec.lparam));
+                    if spec.post {
+                        PostMessageW(hwnd, spec.msg, wparam, lparam);
+                    } else {
+                        SendMessageW(hwnd, spec.msg, wparam, lparam);
                     }
-                },
-                ItemKind::Menu(index, sub_index) => unsafe {
-                    let m = GetSubMenu(GetMenu(mmd_window), *index as _);
-                    let state = GetMenuState(m, *sub_index as _, MF_BYPOSITION);
-                    if (state & MFS_DISABLED.0) == 0 {
-                        PostMessageA(
-                            mmd_window,
-                            WM_COMMAND,
-                            WPARAM(GetMenuItemID(m, *sub_index as _) as _),
-                            LPARAM(0),
-                        );
-                        log::debug!("Menu: {}, {}", index, sub_index);
+                    log::debug!("Message: 0x{:x} 0x{:x}", spec.id, spec.msg);
+                }
+            },
+            ItemKind::Click(target, x, y) => unsafe {
+                let hwnd = match find_window(mmd_window, sub_window, *target, &self.tables.classes, |_| true) {
+                    Some(hwnd) => hwnd,
+                    None => return,
+                };
+                if !IsWindowEnabled(hwnd).as_bool() {
+                    return;
+                }
+                let pt = client_point(hwnd, *x, *y);
+                PostMessageW(hwnd, WM_LBUTTONDOWN, WPARAM(MK_LBUTTON as _), point_to_lparam(pt));
+                PostMessageW(hwnd, WM_LBUTTONUP, WPARAM(0), point_to_lparam(pt));
+                log::debug!("Click: {:?} {} {}", target, pt.x, pt.y);
+            },
+            ItemKind::Popup(target, x, y, text) => unsafe {
+                let hwnd = match find_window(mmd_window, sub_window, *target, &self.tables.classes, |_| true) {
+                    Some(hwnd) => hwnd,
+                    None => return,
+                };
+                if !IsWindowEnabled(hwnd).as_bool() {
+                    return;
+                }
+                let pt = client_point(hwnd, *x, *y);
+                self.pending_popup = match text {
+                    Some(TableIndex::Resolved(text)) => Some(PendingPopup {
+                        target: hwnd,
+                        text: *text,
+                        armed: false,
+                        at: Instant::now(),
+                    }),
+                    Some(TableIndex::Unresolved) => {
+                        log::error!("Popup: unresolved caption");
+                        return;
                     }
-                },
-                ItemKind::Fold(hide_id, show_id) => unsafe {
-                    let hide = GetDlgItem(mmd_window, *hide_id as _);
-                    if IsWindowVisible(hide).as_bool() {
-                        PostMessageW(hide, BM_CLICK, WPARAM(0), LPARAM(0));
-                        log::debug!("Fold: 0x{:x}", hide_id);
-                    } else {
-                        let show = GetDlgItem(mmd_window, *show_id as _);
-                        PostMessageW(show, BM_CLICK, WPARAM(0), LPARAM(0));
-                        log::debug!("Fold: 0x{:x}", show_id);
+                    None => None,
+                };
+                PostMessageW(hwnd, WM_RBUTTONDOWN, WPARAM(MK_RBUTTON as _), point_to_lparam(pt));
+                PostMessageW(hwnd, WM_RBUTTONUP, WPARAM(0), point_to_lparam(pt));
+                log::debug!("Popup: {:?} {} {}", target, pt.x, pt.y);
+            },
+            ItemKind::View(op, dx, dy) => {
+                let view = view_rect(mmd_window, &self.view_controls);
+                post_view_operation(mmd_window, &view, *op, *dx, *dy);
+                log::debug!("View: {:?} {} {}", op, dx, dy);
+            }
+            ItemKind::Scroll(op, target) => unsafe {
+                let vertical = op.is_vertical();
+                // A scroll bar control is chosen only if its orientation matches the operation.
+                let accept = |hwnd| {
+                    !get_class_name(hwnd).eq_ignore_ascii_case("ScrollBar")
+                        || ((GetWindowLongW(hwnd, GWL_STYLE) as u32 & SBS_VERT as u32) != 0) == vertical
+                };
+                let hwnd = match find_window(mmd_window, sub_window, *target, &self.tables.classes, accept) {
+                    Some(hwnd) => hwnd,
+                    None => return,
+                };
+                let code = match op {
+                    ScrollOp::Up | ScrollOp::Left => SB_LINEUP,
+                    ScrollOp::Down | ScrollOp::Right => SB_LINEDOWN,
+                    ScrollOp::PageUp | ScrollOp::PageLeft => SB_PAGEUP,
+                    ScrollOp::PageDown | ScrollOp::PageRight => SB_PAGEDOWN,
+                    ScrollOp::Top | ScrollOp::LeftEnd => SB_TOP,
+                    ScrollOp::Bottom | ScrollOp::RightEnd => SB_BOTTOM,
+                };
+                let msg = if vertical { WM_VSCROLL } else { WM_HSCROLL };
+                // A scroll bar control notifies its parent, other windows scroll their own scroll bars.
+                let (dest, lparam) = if get_class_name(hwnd).eq_ignore_ascii_case("ScrollBar") {
+                    (GetParent(hwnd), LPARAM(hwnd.0))
+                } else {
+                    (hwnd, LPARAM(0))
+                };
+                PostMessageW(dest, msg, WPARAM(code as _), lparam);
+                PostMessageW(dest, msg, WPARAM(SB_ENDSCROLL as _), lparam);
+                log::debug!("Scroll: {:?}", op);
+            },
+            ItemKind::Snapshot(op, register, n) => unsafe {
+                let panel = match self.panels.iter().find(|panel| panel.register == Some(*register)) {
+                    Some(panel) => panel,
+                    None => return,
+                };
+                let button = get_control(mmd_window, sub_window, *register);
+                if !IsWindowVisible(button).as_bool() {
+                    return;
+                }
+                if *op == SnapshotOp::Save {
+                    let values = panel
+                        .fields
+                        .iter()
+                        .map(|id| (*id, get_window_text(get_control(mmd_window, sub_window, *id))))
+                        .collect();
+                    self.snapshots.set(*register, *n, values);
+                    log::debug!("Snapshot: save 0x{:x} {}", register, n);
+                    return;
+                }
+                let snapshot = match self.snapshots.get(*register, *n) {
+                    Some(snapshot) => snapshot,
+                    None => return,
+                };
+                for (id, text) in &snapshot.values {
+                    let hwnd = get_control(mmd_window, sub_window, *id);
+                    if IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool() {
+                        set_window_text(hwnd, text);
+                        commit_edit(hwnd);
                     }
-                },
-                ItemKind::KillFocus => unsafe {
-                    SetFocus(mmd_window);
-                    log::debug!("KillFocus");
-                },
-                ItemKind::FoldAll => unsafe {
-                    for id in folds {
-                        let hwnd = GetDlgItem(mmd_window, *id as _);
-                        if IsWindowVisible(hwnd).as_bool() {
-                            PostMessageW(hwnd, BM_CLICK, WPARAM(0), LPARAM(0));
-                        }
+                }
+                if *op == SnapshotOp::LoadAndRegister && IsWindowEnabled(button).as_bool() {
+                    post_click(button, &mut self.caused_commands);
+                }
+                log::debug!("Snapshot: load 0x{:x} {}", register, n);
+            },
+            ItemKind::Field(dir) => unsafe {
+                let focus = GetFocus();
+                let current = GetDlgCtrlID(focus) as u32;
+                if !is_edit(focus) || get_control(mmd_window, sub_window, current) != focus {
+                    return;
+                }
+                let available = |id| {
+                    let hwnd = get_control(mmd_window, sub_window, id);
+                    IsWindowVisible(hwnd).as_bool() && IsWindowEnabled(hwnd).as_bool()
+                };
+                let next = self
+                    .panels
+                    .iter()
+                    .find_map(|panel| cycle_field(&panel.fields, current, *dir, available));
+                if let Some(next) = next {
+                    self.leave_edit(get_control(mmd_window, sub_window, next));
+                    log::debug!("Field: 0x{:x}", next);
+                }
+            },
+            ItemKind::KillFocus => self.kill_focus(mmd_window),
+            ItemKind::FoldAll => unsafe {
+                for id in &self.folds {
+                    let hwnd = GetDlgItem(mmd_window, *id as _);
+                    if IsWindowVisible(hwnd).as_bool() {
+                        post_click(hwnd, &mut self.caused_commands);
                     }
-                    log::debug!("FoldAll");
-                },
-                ItemKind::UnfoldAll => unsafe {
-                    for id in unfolds {
-                        let hwnd = GetDlgItem(mmd_window, *id as _);
-                        if IsWindowVisible(hwnd).as_bool() {
-                            PostMessageW(hwnd, BM_CLICK, WPARAM(0), LPARAM(0));
-                        }
+                }
+                log::debug!("FoldAll");
+            },
+            ItemKind::UnfoldAll => unsafe {
+                for id in &self.unfolds {
+                    let hwnd = GetDlgItem(mmd_window, *id as _);
+                    if IsWindowVisible(hwnd).as_bool() {
+                        post_click(hwnd, &mut self.caused_commands);
                     }
-                    log::debug!("UnfoldAll");
-                },
+                }
+                log::debug!("UnfoldAll");
+            },
+            ItemKind::Macro(index) => self.start_macro(*index, mmd_window),
+            ItemKind::Launch(index) => {
+                if self.waiting_launchers.contains(index) {
+                    log::debug!("Launcher: already running");
+                } else if self.launchers[*index].launch(mmd_window, *index) {
+                    self.waiting_launchers.insert(*index);
+                }
             }
         }
+    }
+
+    fn start_macro(&mut self, index: usize, mmd_window: HWND) {
+        if self.runner.is_some() {
+            log::debug!("Macro: already running");
+            return;
+        }
+        let delay = self.macros[index].first().map_or(0, |step| step.delay);
+        self.runner = Some(Runner {
+            macro_index: index,
+            index: 0,
+            next_at: Instant::now() + Duration::from_millis(delay as _),
+            deadline: None,
+            pressed: None,
+        });
+        unsafe {
+            SetTimer(mmd_window, MACRO_TIMER_ID, 10, None);
+        }
+        log::debug!("Macro: start {}", index);
+    }
+
+    fn stop_macro(&mut self, mmd_window: HWND) {
+        if let Some(k) = self.runner.take().and_then(|runner| runner.pressed) {
+            self.release_key(k, mmd_window);
+        }
+        unsafe {
+            KillTimer(mmd_window, MACRO_TIMER_ID);
+        }
+    }
+
+    fn press_key(&mut self, k: u32, mmd_window: HWND) {
+        self.handle(ItemKind::Key(k), mmd_window, None);
+        unsafe {
+            PostMessageW(mmd_window, WM_KEYDOWN, WPARAM(k as _), LPARAM(SYNTHESIZED_KEY | 1));
+        }
+    }
 
+    /// Presses and releases the key of a combo once, for macros and momentary actions.
+    ///
+    /// The combo is reported by `is_pressed` until the posted key-up is processed.
+    fn tap_key_combo(&mut self, spec: KeyComboSpec, mmd_window: HWND) {
+        self.handle(ItemKind::KeyCombo(spec), mmd_window, None);
+        unsafe {
+            PostMessageW(
+                mmd_window,
+                WM_KEYDOWN,
+                WPARAM(spec.key as _),
+                LPARAM(SYNTHESIZED_KEY | 1),
+            );
+            PostMessageW(
+                mmd_window,
+                WM_KEYUP,
+                WPARAM(spec.key as _),
+                LPARAM(SYNTHESIZED_KEY | (3 << 30) | 1),
+            );
+        }
+    }
+
+    fn release_key(&mut self, k: u32, mmd_window: HWND) {
+        if let Some(ks) = self.key_states.get_mut(&k) {
+            *ks = false;
+        }
+        unsafe {
+            PostMessageW(
+                mmd_window,
+                WM_KEYUP,
+                WPARAM(k as _),
+                LPARAM(SYNTHESIZED_KEY | (3 << 30) | 1),
+            );
+        }
+    }
+
+    /// Advances the running macro. Called on every tick of `MACRO_TIMER_ID`.
+    pub fn tick(&mut self, mmd_window: HWND, sub_window: Option<HWND>) {
+        let now = Instant::now();
+        let (step, pressed) = match self.runner.as_mut() {
+            Some(runner) => (
+                self.macros[runner.macro_index].get(runner.index).cloned(),
+                runner.pressed.take(),
+            ),
+            None => return self.stop_macro(mmd_window),
+        };
+        if let Some(k) = pressed {
+            self.release_key(k, mmd_window);
+        }
+        let step = match step {
+            Some(step) => step,
+            None => {
+                log::debug!("Macro: end");
+                return self.stop_macro(mmd_window);
+            }
+        };
+        let runner = self.runner.as_mut().unwrap();
+        // The steps after a launcher with `wait` are held until the program exits.
+        if now < runner.next_at || !self.waiting_launchers.is_empty() {
+            return;
+        }
+        if !is_available(&step.kind, mmd_window, sub_window) {
+            match step.wait {
+                Some(wait) => {
+                    let deadline = *runner.deadline.get_or_insert(now + Duration::from_millis(wait as _));
+                    if now >= deadline {
+                        log::debug!("Macro: timeout");
+                        self.stop_macro(mmd_window);
+                    }
+                }
+                None => {
+                    log::debug!("Macro: abort");
+                    self.stop_macro(mmd_window);
+                }
+            }
+            return;
+        }
+        runner.index += 1;
+        runner.deadline = None;
+        let delay = self.macros[runner.macro_index]
+            .get(runner.index)
+            .map_or(0, |step| step.delay);
+        runner.next_at = now + Duration::from_millis(delay as _);
+        match step.kind {
+            ItemKind::Key(k) => {
+                runner.pressed = Some(k);
+                self.press_key(k, mmd_window);
+            }
+            ItemKind::KeyCombo(spec) => self.tap_key_combo(spec, mmd_window),
+            kind => self.handle(kind, mmd_window, sub_window),
+        }
+        if is_recorded(&step.kind) {
+            self.dispatched.push(step.id);
+        }
+    }
+
+    /// Returns whether the keys pressed with `vk` are bound to an action.
+    pub fn is_bound(&mut self, vk: u32) -> bool {
+        get_keyboard_state(&mut self.input);
+        let mut keys = Keys::with_capacity(3);
+        keys.keyboard_state(&self.input);
+        if self.handler.contains_key(&keys) {
+            return true;
+        }
+        keys.vk(vk);
+        self.handler.contains_key(&keys)
+    }
+
+    pub fn key_down(&mut self, vk: u32, repeat: bool, mmd_window: HWND, sub_window: Option<HWND>, hwnd: HWND) {
         get_keyboard_state(&mut self.input);
         self.input_keys.keyboard_state(&self.input);
         log::debug!("key_down input_keys = {:?}", self.input_keys);
-        if get_class_name(hwnd).to_ascii_uppercase() == "EDIT" {
+        if is_edit(hwnd) {
             if self.input_keys == Keys::from_slice(&[VK_TAB.0 as u32]) {
                 self.tabstop = true;
                 return;
             }
         }
-        if let Some(item) = self.handler.get(&self.input_keys) {
-            handle(
-                item,
-                &mut self.key_states,
-                &self.folds,
-                &self.unfolds,
-                mmd_window,
-                sub_window,
-                hwnd,
-            );
+        if !self.handler.contains_key(&self.input_keys) {
+            self.input_keys.vk(vk);
+        }
+        let action = match self.handler.get(&self.input_keys) {
+            Some(action) => action,
+            None => return,
+        };
+        if is_edit(hwnd) && !action.in_edit {
             return;
         }
-        self.input_keys.vk(vk);
-        if let Some(item) = self.handler.get(&self.input_keys) {
-            handle(
-                item,
-                &mut self.key_states,
-                &self.folds,
-                &self.unfolds,
-                mmd_window,
-                sub_window,
-                hwnd,
-            );
+        let now = Instant::now();
+        let fire = match self.held.as_mut() {
+            Some(held) if repeat && held.keys == self.input_keys => {
+                let interval = match action.repeat {
+                    Some(r) => r.interval_at((now - held.pressed_at).as_millis() as _),
+                    None => Some(0),
+                };
+                match interval {
+                    Some(interval) if now - held.fired_at >= Duration::from_millis(interval as _) => {
+                        held.fired_at = now;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => {
+                self.held = Some(Held {
+                    keys: self.input_keys.clone(),
+                    pressed_at: now,
+                    fired_at: now,
+                });
+                true
+            }
+        };
+        if !fire {
+            if let ItemKind::Key(k) = action.kind {
+                if let Some(ks) = self.key_states.get_mut(&k) {
+                    *ks = false;
+                }
+            }
+            log::debug!("suppress repeat");
+            return;
+        }
+        if let Some(m) = action.momentary.as_ref() {
+            if self.momentaries.iter().any(|active| active.keys == self.input_keys) {
+                return;
+            }
+            let release = m.release.clone().or_else(|| {
+                m.restore
+                    .iter()
+                    .find(|(_, item)| is_checked(item, mmd_window, sub_window) == Some(true))
+                    .cloned()
+            });
+            self.momentaries.push(ActiveMomentary {
+                keys: self.input_keys.clone(),
+                release,
+                group: m.release.iter().chain(&m.restore).map(|(id, _)| id.clone()).collect(),
+            });
         }
+        let multiplier = action
+            .multiplier
+            .filter(|m| (self.input[m.key as usize & 0xff] & 0x80) != 0)
+            .map(|m| m.factor);
+        let kind = match (action.kind, multiplier) {
+            (ItemKind::Frame(jump, id, n), Some(factor)) => ItemKind::Frame(jump, id, n.saturating_mul(factor)),
+            (ItemKind::Step(spec), Some(factor)) => ItemKind::Step(StepSpec {
+                step: spec.step * factor as f64,
+                ..spec
+            }),
+            (ItemKind::View(op, dx, dy), Some(factor)) => {
+                ItemKind::View(op, dx.saturating_mul(factor), dy.saturating_mul(factor))
+            }
+            (ItemKind::Trackbar(TrackbarOp::Step(step), id), Some(factor)) => {
+                ItemKind::Trackbar(TrackbarOp::Step(step.saturating_mul(factor)), id)
+            }
+            (kind, _) => kind,
+        };
+        let id = (is_recorded(&kind) && !action.parameterized && multiplier.is_none()).then(|| action.id.clone());
+        self.handle(kind, mmd_window, sub_window);
+        self.dispatched.extend(id);
     }
 
-    pub fn key_up(&mut self, vk: u32) {
+    pub fn key_up(&mut self, vk: u32, mmd_window: HWND, sub_window: Option<HWND>) {
         get_keyboard_state(&mut self.input);
         self.input_keys.keyboard_state(&self.input);
         log::debug!("key_up input_keys = {:?}", self.input_keys);
-        for (keys, kind) in self.handler.iter() {
-            if let ItemKind::Key(k) = kind {
-                if !keys.is_included(&self.input_keys) {
+        // The released momentary actions are undone from the latest one.
+        let mut i = self.momentaries.len();
+        while i > 0 {
+            i -= 1;
+            if self.momentaries[i].keys.is_included(&self.input_keys) {
+                continue;
+            }
+            let active = self.momentaries.remove(i);
+            // A momentary action released under a later one of the same group hands over what to restore.
+            if let Some(later) = self.momentaries[i..].iter_mut().find(|later| later.same_group(&active)) {
+                later.release = active.release;
+            } else if let Some((id, release)) = active.release {
+                log::debug!("Momentary: release");
+                match release {
+                    ItemKind::KeyCombo(spec) => self.tap_key_combo(spec, mmd_window),
+                    kind => self.handle(kind, mmd_window, sub_window),
+                }
+                if is_recorded(&release) {
+                    self.dispatched.push(id);
+                }
+            }
+        }
+        if self
+            .held
+            .as_ref()
+            .map_or(false, |held| !held.keys.is_included(&self.input_keys))
+        {
+            self.held = None;
+        }
+        for (keys, action) in self.handler.iter() {
+            if keys.is_included(&self.input_keys) {
+                continue;
+            }
+            match &action.kind {
+                ItemKind::Key(k) => {
                     if let Some(ks) = self.key_states.get_mut(k) {
                         *ks = false;
                     }
                 }
+                ItemKind::KeyCombo(spec) if self.key_combo == Some(*spec) => self.key_combo = None,
+                _ => {}
             }
         }
         if vk == VK_TAB.0 as u32 && self.tabstop {
@@ -241,10 +1496,207 @@ impl Handler {
         }
     }
 
+    #[inline]
+    pub fn set_kill_focus_mode(&mut self, mode: KillFocusMode) {
+        self.kill_focus_mode = mode;
+    }
+
+    /// Remembers the text of an edit control that got the focus to revert it.
+    #[inline]
+    pub fn edit_focused(&mut self, hwnd: HWND) {
+        self.edit_text = Some((hwnd, get_window_text(hwnd)));
+    }
+
+    /// Updates the remembered text of the focused edit control when its text is committed by Enter.
+    #[inline]
+    pub fn edit_committed(&mut self, hwnd: HWND) {
+        if let Some((_, text)) = self.edit_text.as_mut().filter(|(h, _)| *h == hwnd) {
+            *text = get_window_text(hwnd);
+        }
+    }
+
+    /// Adjusts the value of a focused numeric edit control like a spin box by `delta` steps.
+    ///
+    /// `coarse` multiplies the step by 10 and `fine` by 0.1. `fine` is ignored for integer fields such as frames,
+    /// whose step is an integer and whose text has no decimal point.
+    /// Returns `false` if the control has no step.
+    pub fn scrub(
+        &mut self,
+        hwnd: HWND,
+        delta: f64,
+        coarse: bool,
+        fine: bool,
+        mmd_window: HWND,
+        sub_window: Option<HWND>,
+    ) -> bool {
+        let spec = match self.scrubs.get(&(unsafe { GetDlgCtrlID(hwnd) } as u32)) {
+            Some(spec) => spec,
+            None => return false,
+        };
+        // The control ID alone may match an edit control of a dialog or a plugin window.
+        if get_control(mmd_window, sub_window, spec.id) != hwnd {
+            return false;
+        }
+        let text = get_window_text(hwnd);
+        let integer = spec.step.fract() == 0.0 && !text.contains('.');
+        let scale = match (coarse, fine) {
+            (true, _) => 10.0,
+            (false, true) if !integer => 0.1,
+            _ => 1.0,
+        };
+        let current = text.trim().parse::<f64>().unwrap_or(0.0);
+        let value = (current + spec.step * scale * delta).clamp(spec.min, spec.max);
+        set_window_text(hwnd, format_value(value));
+        commit_edit(hwnd);
+        log::debug!("scrub: 0x{:x} = {}", spec.id, value);
+        true
+    }
+
+    /// Returns the text of the focused edit control before it was edited.
+    #[inline]
+    pub fn edit_text(&self, hwnd: HWND) -> Option<&str> {
+        self.edit_text
+            .as_ref()
+            .filter(|(h, _)| *h == hwnd)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// Moves the focus to the main window of MMD according to `KillFocusMode`.
+    #[inline]
+    pub fn kill_focus(&mut self, mmd_window: HWND) {
+        self.leave_edit(mmd_window);
+        log::debug!("KillFocus");
+    }
+
+    /// Moves the focus from the focused edit control to `target` according to `KillFocusMode`.
+    ///
+    /// With `KillFocusMode::Commit` the focus is moved after the posted Enter has been processed,
+    /// unless the focus has been moved elsewhere in the meantime.
+    fn leave_edit(&mut self, target: HWND) {
+        unsafe {
+            let focus = GetFocus();
+            if is_edit(focus) {
+                match self.kill_focus_mode {
+                    KillFocusMode::Leave => {}
+                    KillFocusMode::Commit => {
+                        commit_edit(focus);
+                        self.pending_focus = Some((focus, target));
+                        log::debug!("leave_edit: commit");
+                        return;
+                    }
+                    KillFocusMode::Revert => {
+                        if let Some((_, text)) = self.edit_text.as_ref().filter(|(hwnd, _)| *hwnd == focus) {
+                            set_window_text(focus, text);
+                            log::debug!("leave_edit: revert");
+                        }
+                    }
+                }
+            }
+            SetFocus(target);
+        }
+    }
+
+    /// Takes the IDs of the actions dispatched by keys and macros since the last call, for `Recorder`.
+    #[inline]
+    pub fn take_dispatched(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.dispatched)
+    }
+
+    #[inline]
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    #[inline]
+    pub fn popup_clicked(&mut self, hwnd: HWND) {
+        if let Some(popup) = self.pending_popup.as_mut().filter(|popup| popup.target == hwnd) {
+            popup.armed = true;
+        }
+    }
+
+    /// Returns the caption to select in the popup menu owned by `owner`
+    /// if it was opened by the right click of `ItemKind::Popup` just before.
+    pub fn take_popup_caption(&mut self, owner: HWND) -> Option<&str> {
+        let popup = self.pending_popup.as_ref()?;
+        if popup.at.elapsed() > Duration::from_secs(1) {
+            self.pending_popup = None;
+            return None;
+        }
+        let related = owner == popup.target || unsafe { IsChild(owner, popup.target).as_bool() };
+        if !popup.armed || !related {
+            return None;
+        }
+        let text = popup.text;
+        self.pending_popup = None;
+        Some(&self.tables.texts[text])
+    }
+
+    #[inline]
+    pub fn snapshots(&self) -> &Snapshots {
+        &self.snapshots
+    }
+
+    #[inline]
+    pub fn panel_label(&self, register: u32) -> Option<&str> {
+        self.panels
+            .iter()
+            .find(|panel| panel.register == Some(register))
+            .map(|panel| panel.label.as_str())
+    }
+
+    /// Returns whether a WM_COMMAND with `id` was caused by an action of MMAccel, forgetting it.
+    pub fn take_caused_command(&mut self, id: u32) -> bool {
+        self.caused_commands
+            .retain(|(_, at)| at.elapsed() <= CAUSED_COMMAND_TIMEOUT);
+        match self.caused_commands.iter().position(|(caused, _)| *caused == id) {
+            Some(i) => {
+                self.caused_commands.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    pub fn launcher_exited(&mut self, index: usize) {
+        self.waiting_launchers.remove(&index);
+    }
+
+    /// Tracks keyboard messages posted by MMAccel so that `is_pressed` reports them.
+    #[inline]
+    pub fn synthesized_key(&mut self, vk: u32, down: bool) {
+        self.synthesized = if down { Some(vk) } else { None };
+        if !down && self.key_combo.map_or(false, |spec| spec.key == vk) {
+            self.key_combo = None;
+        }
+        if !down && vk == VK_RETURN.0 as u32 {
+            if let Some((edit, target)) = self.pending_focus.take() {
+                unsafe {
+                    if GetFocus() == edit {
+                        SetFocus(target);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn is_pressed(&self, vk: u32) -> bool {
         if vk == VK_TAB.0 as u32 && self.tabstop {
             return true;
         }
+        if self.synthesized == Some(vk) {
+            return true;
+        }
+        // The modifier keys of the combo are reported as they are in the combo, regardless of `key_states`.
+        if let Some(spec) = self.key_combo.as_ref() {
+            match VIRTUAL_KEY(vk as _) {
+                VK_CONTROL | VK_LCONTROL | VK_RCONTROL => return spec.ctrl,
+                VK_SHIFT | VK_LSHIFT | VK_RSHIFT => return spec.shift,
+                VK_MENU | VK_LMENU | VK_RMENU => return spec.alt,
+                _ if vk == spec.key => return true,
+                _ => {}
+            }
+        }
         *self.key_states.get(&vk).unwrap_or(&false)
     }
 }
@@ -263,4 +1715,102 @@ mod tests {
         assert!(a != b);
         assert!(a == c);
     }
+
+    #[test]
+    fn cycle_fields() {
+        let fields = [1, 2, 3, 4];
+        assert!(cycle_field(&fields, 1, ComboDir::Next, |_| true) == Some(2));
+        assert!(cycle_field(&fields, 4, ComboDir::Next, |_| true) == Some(1));
+        assert!(cycle_field(&fields, 1, ComboDir::Prev, |id| id != 4) == Some(3));
+        assert!(cycle_field(&fields, 2, ComboDir::Next, |id| id == 2).is_none());
+        assert!(cycle_field(&fields, 5, ComboDir::Next, |_| true).is_none());
+    }
+
+    #[test]
+    fn register_items() {
+        let m = MmdMap::from_file("src/mmd_map.json").unwrap();
+        let mut tables = Tables::default();
+        let preset = m.get("MorphEyePreset").unwrap();
+        assert!(register_item(preset, None, None, &mut tables) == ItemKind::Preset(0x1ff, TableIndex::Resolved(0)));
+        assert!(tables.presets[0] == vec![0.0, 0.5, 1.0]);
+        let select = m.get("ModelSelectorSelect").unwrap();
+        let text = "ボーン".to_string();
+        assert!(
+            register_item(select, None, Some(&text), &mut tables)
+                == ItemKind::Combo(ComboOp::Select(TableIndex::Resolved(0)), 0x1b4)
+        );
+        assert!(tables.texts[0] == text);
+        assert!(
+            register_item(select, None, None, &mut tables)
+                == ItemKind::Combo(ComboOp::Select(TableIndex::Unresolved), 0x1b4)
+        );
+        let scroll = m.get("TimelineScrollPageRight").unwrap();
+        let kind = register_item(scroll, None, None, &mut tables);
+        assert!(kind == ItemKind::Scroll(ScrollOp::PageRight, WindowTarget::Class(TableIndex::Resolved(0))));
+        assert!(register_item(scroll, None, None, &mut tables) == kind);
+        assert!(tables.classes.len() == 1);
+        assert!(tables.classes[0].class == "ScrollBar" && tables.classes[0].anchor == Some(0x1a1));
+    }
+
+    #[test]
+    fn nearest_window() {
+        let rect = |left, top, right, bottom| RECT {
+            left,
+            top,
+            right,
+            bottom,
+        };
+        // The frame edit above the timeline, with the scroll bars of the timeline and of another pane.
+        let anchor = rect(0, 0, 50, 20);
+        let candidates = [
+            ("other", rect(500, 300, 700, 317)),
+            ("timeline", rect(0, 300, 300, 317)),
+            ("list", rect(283, 30, 300, 300)),
+        ];
+        assert!(nearest(&anchor, candidates) == Some("list"));
+        assert!(nearest(&anchor, candidates.iter().copied().filter(|(v, _)| *v != "list")) == Some("timeline"));
+        assert!(nearest(&anchor, [("a", rect(0, 30, 10, 40)), ("b", rect(0, 30, 10, 40))]) == Some("a"));
+        assert!(nearest::<&str>(&anchor, []).is_none());
+        assert!(rect_distance(&anchor, &rect(10, 10, 20, 30)) == 0);
+    }
+
+    #[test]
+    fn view_areas() {
+        let client = RECT {
+            left: 0,
+            top: 0,
+            right: 1200,
+            bottom: 800,
+        };
+        let controls = RECT {
+            left: 300,
+            top: 560,
+            right: 900,
+            bottom: 600,
+        };
+        let view = view_area(&client, Some(&controls));
+        assert!((view.left, view.top, view.right, view.bottom) == (300, 0, 1200, 560));
+        let view = view_area(&client, None);
+        assert!((view.left, view.top, view.right, view.bottom) == (0, 0, 1200, 800));
+    }
+
+    #[test]
+    fn momentary_groups() {
+        let active = |group: &[&str]| ActiveMomentary {
+            keys: Keys::new(),
+            release: None,
+            group: group.iter().map(|id| id.to_string()).collect(),
+        };
+        let bone = active(&["BoneSelect", "BoneRotate"]);
+        assert!(bone.same_group(&active(&["BoneRotate"])));
+        assert!(!bone.same_group(&active(&["ViewBottom"])));
+        assert!(!bone.same_group(&active(&[])));
+    }
+
+    #[test]
+    fn menu_captions() {
+        assert!(menu_caption("&Copy\tCtrl+C") == "Copy");
+        assert!(menu_caption("コピー(&C)") == "コピー(C)");
+        assert!(menu_caption("A && B") == "A & B");
+    }
 }
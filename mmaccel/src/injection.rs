@@ -14,10 +14,9 @@ pub fn image_import_desc(base_addr: usize, target: &[u8]) -> windows::core::Resu
             if img_desc.Name == 0 {
                 return Err(get_last_error().into());
             }
-            let p = (base_addr + img_desc.Name as usize) as *const u8;
-            let name = std::slice::from_raw_parts(p, target.len());
-            let name = name.iter().map(|c| c.to_ascii_lowercase()).collect::<Vec<_>>();
-            if name.iter().eq(target) {
+            let p = (base_addr + img_desc.Name as usize) as *const std::os::raw::c_char;
+            let name = std::ffi::CStr::from_ptr(p).to_bytes();
+            if name.eq_ignore_ascii_case(target) {
                 break;
             }
             img_desc_ptr = img_desc_ptr.offset(1);
@@ -41,13 +40,11 @@ pub fn inject_functions(
                 continue;
             }
             let name_ptr = (base_addr + int.u1.AddressOfData as usize) as *const IMAGE_IMPORT_BY_NAME;
+            // The whole NUL-terminated name is compared so that e.g. TrackPopupMenu does not match TrackPopupMenuEx.
+            let name = std::ffi::CStr::from_ptr(name_ptr.as_ref().unwrap().Name.as_ptr() as *const _).to_bytes();
             for &(function_name, fp) in functions.iter() {
-                let name = std::slice::from_raw_parts(
-                    name_ptr.as_ref().unwrap().Name.as_ptr() as *const u8,
-                    function_name.len(),
-                );
                 let mut old_type = PAGE_PROTECTION_FLAGS(0);
-                if name.iter().eq(function_name) {
+                if name == function_name {
                     VirtualProtect(
                         (&mut iat.u1.Function) as *mut _ as _,
                         std::mem::size_of::<u64>() as _,
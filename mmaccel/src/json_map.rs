@@ -0,0 +1,93 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+/// Numbered entries grouped by a key and saved in a JSON file.
+///
+/// Changes are written to the file immediately.
+#[derive(Debug)]
+pub struct JsonMap<K, V> {
+    path: PathBuf,
+    entries: BTreeMap<K, BTreeMap<u32, V>>,
+}
+
+impl<K, V> Default for JsonMap<K, V> {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::new(),
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, V> JsonMap<K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    /// Loads the entries, or starts empty if the file does not exist or is broken.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = File::open(&path)
+            .ok()
+            .and_then(|file| match serde_json::from_reader(BufReader::new(file)) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    log::error!("JsonMap::from_file: {} {:?}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    fn to_file(&self) -> std::io::Result<()> {
+        let file = File::create(&self.path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &self.entries)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn get<Q>(&self, key: &Q, n: u32) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.get(key).and_then(|entries| entries.get(&n))
+    }
+
+    /// Iterates the entries of `key` in the order of the number.
+    #[inline]
+    pub fn iter_key<Q>(&self, key: &Q) -> impl Iterator<Item = (u32, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries
+            .get(key)
+            .into_iter()
+            .flat_map(|entries| entries.iter().map(|(n, v)| (*n, v)))
+    }
+
+    /// Iterates `(key, n, entry)` in the order of the key and the number.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, u32, &V)> {
+        self.entries
+            .iter()
+            .flat_map(|(key, entries)| entries.iter().map(move |(n, v)| (key, *n, v)))
+    }
+
+    /// Replaces the entry `n` of `key` with the one made from the current entry by `f`,
+    /// and writes the file.
+    pub fn update(&mut self, key: K, n: u32, f: impl FnOnce(Option<V>) -> V) {
+        let entries = self.entries.entry(key).or_default();
+        let entry = f(entries.remove(&n));
+        entries.insert(n, entry);
+        if let Err(e) = self.to_file() {
+            log::error!("JsonMap::to_file: {} {:?}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::*;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Posted to the MMD window with the index of the launcher when a program started with `wait` exits.
+///
+/// Only the message to the MMD window is ours, since other windows may use the same value.
+pub const WM_LAUNCHER_EXITED: u32 = WM_APP + 1;
+
+/// An external program started by a binding.
+///
+/// `{project}` and `{mmd_dir}` in `args` are replaced with the path of the current project
+/// and the directory of MikuMikuDance.exe.
+/// The handle of the MMD window is appended to the arguments if `hwnd` is `true`.
+/// If `wait` is `true`, the exit of the program is waited for on another thread
+/// and reported by posting `WM_LAUNCHER_EXITED`.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize)]
+pub struct Launcher {
+    pub name: String,
+    pub program: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub current_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub hwnd: bool,
+    #[serde(default)]
+    pub wait: bool,
+}
+
+impl Launcher {
+    /// Returns whether `WM_LAUNCHER_EXITED` will be posted with `index`.
+    pub fn launch(&self, mmd_window: HWND, index: usize) -> bool {
+        let mmd_dir = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_default();
+        let project = bookmarks::current_project(mmd_window);
+        let mut command = std::process::Command::new(&self.program);
+        command.args(
+            self.args
+                .iter()
+                .map(|arg| expand_arg(arg, &project, &mmd_dir.to_string_lossy())),
+        );
+        if self.hwnd {
+            command.arg(mmd_window.0.to_string());
+        }
+        command.current_dir(self.current_dir.as_deref().unwrap_or(&mmd_dir));
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                log::error!("Launcher: {} {:?}", self.program.display(), e);
+                return false;
+            }
+        };
+        log::debug!("Launcher: {}", self.name);
+        if !self.wait {
+            return false;
+        }
+        let name = self.name.clone();
+        std::thread::spawn(move || {
+            match child.wait() {
+                Ok(status) => log::debug!("Launcher: {} {}", name, status),
+                Err(e) => log::error!("Launcher: {} {:?}", name, e),
+            }
+            unsafe {
+                PostMessageW(mmd_window, WM_LAUNCHER_EXITED, WPARAM(index), LPARAM(0));
+            }
+        });
+        true
+    }
+}
+
+fn expand_arg(arg: &str, project: &str, mmd_dir: &str) -> String {
+    arg.replace("{project}", project).replace("{mmd_dir}", mmd_dir)
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct Launchers(BTreeMap<String, Launcher>);
+
+impl Launchers {
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    #[inline]
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&Launcher> {
+        self.0.get(key.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_launchers() {
+        let json = r#"{
+            "Backup": {
+                "name": "バックアップ",
+                "program": "backup.bat",
+                "args": [ "{project}", "{mmd_dir}\\UserFile" ],
+                "wait": true
+            }
+        }"#;
+        let launchers: Launchers = serde_json::from_str(json).unwrap();
+        let l = launchers.get("Backup").unwrap();
+        assert!(l.name == "バックアップ");
+        assert!(l.wait && !l.hwnd && l.current_dir.is_none());
+        assert!(expand_arg(&l.args[0], "C:\\dance.pmm", "C:\\MMD") == "C:\\dance.pmm");
+        assert!(expand_arg(&l.args[1], "", "C:\\MMD") == "C:\\MMD\\UserFile");
+    }
+}
@@ -1,13 +1,20 @@
 #![allow(clippy::fn_to_numeric_cast)]
 #![allow(clippy::missing_safety_doc)]
 
+mod bookmarks;
 mod context;
+mod expr;
 mod file_monitor;
 mod handler;
 mod injection;
+mod json_map;
+mod launchers;
+mod macros;
 mod menu;
 mod mmd;
 mod mmd_map;
+mod recorder;
+mod snapshots;
 
 /*
 use bindings::Windows::Win32::{
@@ -25,8 +32,8 @@ use menu::*;
 use once_cell::sync::OnceCell;
 use windows::Win32::{
     Foundation::*, Media::*, Storage::FileSystem::*, System::Diagnostics::Debug::*, System::Memory::*,
-    System::SystemServices::*, System::WindowsProgramming::*, System::IO::*, UI::Input::KeyboardAndMouse::*,
-    UI::WindowsAndMessaging::*,
+    System::SystemServices::*, System::WindowsProgramming::*, System::IO::*, UI::Controls::*,
+    UI::Input::KeyboardAndMouse::*, UI::WindowsAndMessaging::*,
 };
 use wrapper::*;
 
@@ -79,6 +86,37 @@ unsafe extern "system" fn proxy_get_key_state(vk: i32) -> i16 {
     }
 }
 
+unsafe extern "system" fn proxy_track_popup_menu(
+    menu: HMENU,
+    flags: TRACK_POPUP_MENU_FLAGS,
+    x: i32,
+    y: i32,
+    reserved: i32,
+    hwnd: HWND,
+    rc: *const RECT,
+) -> BOOL {
+    if let Some(ret) = CONTEXT.get_mut().unwrap().track_popup_menu(menu, flags.0, hwnd) {
+        ret
+    } else {
+        TrackPopupMenu(menu, flags, x, y, reserved, hwnd, rc)
+    }
+}
+
+unsafe extern "system" fn proxy_track_popup_menu_ex(
+    menu: HMENU,
+    flags: u32,
+    x: i32,
+    y: i32,
+    hwnd: HWND,
+    params: *const TPMPARAMS,
+) -> BOOL {
+    if let Some(ret) = CONTEXT.get_mut().unwrap().track_popup_menu(menu, flags, hwnd) {
+        ret
+    } else {
+        TrackPopupMenuEx(menu, flags, x, y, hwnd, params)
+    }
+}
+
 fn build_logger(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error + 'static>> {
     const FORMAT: &str = "[{d(%Y-%m-%d %H:%M:%S%z)} {l} (({f}:{L}))] {m}\n";
     let stdout = ConsoleAppender::builder()
@@ -135,7 +173,11 @@ pub unsafe extern "system" fn mmaccel_run(base_addr: usize) {
         return;
     }
     let user32 = user32.unwrap();
-    let functions: &[(&[u8], u64)] = &[(b"GetKeyState", proxy_get_key_state as u64)];
+    let functions: &[(&[u8], u64)] = &[
+        (b"GetKeyState", proxy_get_key_state as u64),
+        (b"TrackPopupMenu", proxy_track_popup_menu as u64),
+        (b"TrackPopupMenuEx", proxy_track_popup_menu_ex as u64),
+    ];
     if inject_functions(base_addr, &user32, &functions).is_err() {
         error("MMAccelの読み込みに失敗しました");
     }
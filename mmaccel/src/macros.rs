@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+/// A step of a macro.
+///
+/// `delay` is the time in milliseconds to wait before the step.
+/// `wait` is the time in milliseconds to wait for the target control to become enabled,
+/// the macro is aborted immediately if it is `None`.
+#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize)]
+pub struct Step {
+    pub action: String,
+    #[serde(default)]
+    pub delay: u32,
+    #[serde(default)]
+    pub wait: Option<u32>,
+}
+
+impl Step {
+    #[inline]
+    pub fn new(action: impl AsRef<str>) -> Self {
+        Self {
+            action: action.as_ref().to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl serde::Serialize for Step {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        if self.delay == 0 && self.wait.is_none() {
+            return serializer.serialize_str(&self.action);
+        }
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("action", &self.action)?;
+        if self.delay != 0 {
+            map.serialize_entry("delay", &self.delay)?;
+        }
+        if let Some(wait) = self.wait {
+            map.serialize_entry("wait", &wait)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StepRepr {
+    Action(String),
+    Step(Step),
+}
+
+fn deserialize_steps<'de, D>(deserializer: D) -> Result<Vec<Step>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let steps: Vec<StepRepr> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(steps
+        .into_iter()
+        .map(|step| match step {
+            StepRepr::Action(action) => Step::new(action),
+            StepRepr::Step(step) => step,
+        })
+        .collect())
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Macro {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_steps")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Macros(BTreeMap<String, Macro>);
+
+impl Macros {
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&Macro> {
+        self.0.get(key.as_ref())
+    }
+
+    /// Adds a recorded macro with a new ID and returns the ID.
+    pub fn push_recorded(&mut self, steps: Vec<Step>) -> String {
+        let n = (1..).find(|n| !self.0.contains_key(&format!("Recorded{}", n))).unwrap();
+        let id = format!("Recorded{}", n);
+        self.0.insert(
+            id.clone(),
+            Macro {
+                name: format!("記録マクロ{}", n),
+                steps,
+            },
+        );
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_macros() {
+        let json = r#"{
+            "RegisterAll": {
+                "name": "全て登録",
+                "steps": [ "JumpCurrentFrame", { "action": "BoneAllSelect", "delay": 100 }, { "action": "BoneRegister", "wait": 1000 } ]
+            }
+        }"#;
+        let macros: Macros = serde_json::from_str(json).unwrap();
+        let m = macros.get("RegisterAll").unwrap();
+        assert!(m.name == "全て登録");
+        assert!(m.steps[0] == Step::new("JumpCurrentFrame"));
+        assert!(m.steps[1].delay == 100 && m.steps[1].wait.is_none());
+        assert!(m.steps[2].wait == Some(1000));
+        let ret = serde_json::to_value(&macros).unwrap();
+        assert!(ret["RegisterAll"]["steps"][0] == "JumpCurrentFrame");
+        assert!(ret["RegisterAll"]["steps"][1]["delay"] == 100);
+    }
+
+    #[test]
+    fn push_recorded() {
+        let mut macros = Macros::default();
+        assert!(macros.push_recorded(vec![Step::new("Undo")]) == "Recorded1");
+        assert!(macros.push_recorded(vec![Step::new("Redo")]) == "Recorded2");
+        assert!(macros.get("Recorded2").unwrap().name == "記録マクロ2");
+    }
+}
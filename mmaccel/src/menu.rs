@@ -103,6 +103,25 @@ impl<T: MenuCommand> MenuBuilder<T> {
         }
     }
 
+    /// Adds a submenu whose items are managed by the caller.
+    #[inline]
+    pub fn sub_menu(mut self, text: impl AsRef<str>, menu: HMENU) -> Self {
+        unsafe {
+            let name = to_wchar(text);
+            let info = MENUITEMINFOW {
+                cbSize: std::mem::size_of::<MENUITEMINFOW>() as _,
+                fMask: MIIM_TYPE | MIIM_SUBMENU,
+                fType: MFT_STRING,
+                dwTypeData: PWSTR(name.as_ptr() as _),
+                hSubMenu: menu,
+                ..Default::default()
+            };
+            InsertMenuItemW(self.menu, self.index, true, &info);
+            self.index += 1;
+            self
+        }
+    }
+
     #[inline]
     pub fn separator(mut self) -> Self {
         unsafe {
@@ -124,6 +143,7 @@ impl<T: MenuCommand> MenuBuilder<T> {
             DrawMenuBar(self.hwnd);
             Menu {
                 menu: self.menu,
+                next_id: ROOT_ID + self.id,
                 table: self.table,
             }
         }
@@ -132,10 +152,19 @@ impl<T: MenuCommand> MenuBuilder<T> {
 
 pub struct Menu<T> {
     menu: HMENU,
+    next_id: u32,
     table: Vec<(std::mem::Discriminant<T>, std::mem::Discriminant<MenuItemType>)>,
 }
 
 impl<T: MenuCommand> Menu<T> {
+    /// Reserves `count` command IDs following the items for the submenus managed by the caller.
+    #[inline]
+    pub fn reserve_ids(&mut self, count: u32) -> std::ops::Range<u32> {
+        let ids = self.next_id..self.next_id + count;
+        self.next_id = ids.end;
+        ids
+    }
+
     #[inline]
     fn is_checked_item(&self, id: u32) -> bool {
         unsafe {
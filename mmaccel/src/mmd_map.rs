@@ -8,32 +8,329 @@ pub enum ComboDir {
     Next,
 }
 
+/// An index into a table of `Handler` for data that an `ItemKind` cannot hold.
+///
+/// Items are parsed as `Unresolved` and `Handler` resolves them when it registers the bindings.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TableIndex {
+    Unresolved,
+    Resolved(usize),
+}
+
+/// `Select` indexes the texts given by the bindings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComboOp {
+    Prev,
+    Next,
+    PrevWrap,
+    NextWrap,
+    First,
+    Last,
+    Select(TableIndex),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FrameJump {
+    Relative,
+    Absolute,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TrackbarOp {
+    Step(i32),
+    Set(i32),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScrollOp {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    PageLeft,
+    PageRight,
+    LeftEnd,
+    RightEnd,
+}
+
+impl ScrollOp {
+    fn new(s: &str) -> Option<Self> {
+        let op = match s {
+            "up" => Self::Up,
+            "down" => Self::Down,
+            "page_up" => Self::PageUp,
+            "page_down" => Self::PageDown,
+            "top" => Self::Top,
+            "bottom" => Self::Bottom,
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "page_left" => Self::PageLeft,
+            "page_right" => Self::PageRight,
+            "left_end" => Self::LeftEnd,
+            "right_end" => Self::RightEnd,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    #[inline]
+    pub fn is_vertical(&self) -> bool {
+        matches!(
+            self,
+            Self::Up | Self::Down | Self::PageUp | Self::PageDown | Self::Top | Self::Bottom
+        )
+    }
+}
+
+/// A child window of MMD given by the control ID or the class name.
+///
+/// `Class` indexes the class names registered by `Handler`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowTarget {
+    Id(u32),
+    Class(TableIndex),
+}
+
+/// Mouse operations on the 3D view. `Zoom` uses only the first amount as wheel notches.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViewOp {
+    Orbit,
+    Pan,
+    Zoom,
+}
+
+/// A key pressed together with modifier keys.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct KeyComboSpec {
+    pub key: u32,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyComboSpec {
+    /// `modifiers` is like `"ctrl+shift"`.
+    fn new(key: u32, modifiers: &str) -> Option<Self> {
+        let mut spec = Self {
+            key,
+            ..Default::default()
+        };
+        for m in modifiers.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+            match m.to_ascii_lowercase().as_str() {
+                "ctrl" => spec.ctrl = true,
+                "shift" => spec.shift = true,
+                "alt" => spec.alt = true,
+                _ => return None,
+            }
+        }
+        Some(spec)
+    }
+}
+
+/// A message sent (`post == false`) or posted to the control `id`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MessageSpec {
+    pub id: u32,
+    pub msg: u32,
+    pub wparam: usize,
+    pub lparam: isize,
+    pub post: bool,
+}
+
+/// Parses an integer, or a string as a hex number such as `"111"` or `"0x111"`.
+fn integer(v: &Value) -> Option<i64> {
+    match v {
+        Value::String(s) => {
+            let s = s.trim();
+            match s.strip_prefix('-') {
+                Some(s) => i64::from_str_radix(s.trim_start_matches("0x"), 16).ok().map(|v| -v),
+                None => i64::from_str_radix(s.trim_start_matches("0x"), 16).ok(),
+            }
+        }
+        _ => v.as_i64(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotOp {
+    Save,
+    Load,
+    LoadAndRegister,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BookmarkOp {
+    Set(u32),
+    Go(u32),
+    Next,
+    Prev,
+}
+
+/// Adds `step` to the value of the edit control `id` and clamps it to `min..=max`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StepSpec {
+    pub id: u32,
+    pub step: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ItemKind {
     Key(u32),
+    KeyCombo(KeyComboSpec),
     Button(u32),
     Edit(u32),
-    Combo(ComboDir, u32),
+    Combo(ComboOp, u32),
     Menu(u32, u32),
+    ButtonCheck(u32, bool),
+    MenuCheck(u32, u32, bool),
     Fold(u32, u32),
+    Frame(FrameJump, u32, i32),
+    Bookmark(BookmarkOp, u32),
+    Step(StepSpec),
+    Preset(u32, TableIndex),
+    Trackbar(TrackbarOp, u32),
+    Scroll(ScrollOp, WindowTarget),
+    View(ViewOp, i32, i32),
+    Click(WindowTarget, i32, i32),
+    /// Right-clicks at the position and selects the item of the popup menu by the caption in `Handler`.
+    /// The menu is just shown if the binding gives no caption.
+    Popup(WindowTarget, i32, i32, Option<TableIndex>),
+    Message(MessageSpec),
+    Snapshot(SnapshotOp, u32, u32),
+    Field(ComboDir),
     KillFocus,
     FoldAll,
     UnfoldAll,
+    Macro(usize),
+    Launch(usize),
 }
 
 impl ItemKind {
     fn new(a: &[serde_json::Value]) -> Option<Self> {
         let kind = match a[1].as_str()? {
             "key" if a.len() == 3 => Self::Key(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "key_combo" if a.len() == 4 => Self::KeyCombo(KeyComboSpec::new(
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                a[3].as_str()?,
+            )?),
             "button" if a.len() == 3 => Self::Button(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
             "edit" if a.len() == 3 => Self::Edit(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
-            "combo_prev" if a.len() == 3 => Self::Combo(ComboDir::Prev, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
-            "combo_next" if a.len() == 3 => Self::Combo(ComboDir::Next, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "combo_prev" if a.len() == 3 => Self::Combo(ComboOp::Prev, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "combo_next" if a.len() == 3 => Self::Combo(ComboOp::Next, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "combo_first" if a.len() == 3 => Self::Combo(ComboOp::First, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "combo_last" if a.len() == 3 => Self::Combo(ComboOp::Last, u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            "combo_select" if a.len() == 3 => Self::Combo(
+                ComboOp::Select(TableIndex::Unresolved),
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+            ),
             "menu" if a.len() == 4 => Self::Menu(a[2].as_u64()? as _, a[3].as_u64()? as _),
+            "button_check" if a.len() == 4 => {
+                Self::ButtonCheck(u32::from_str_radix(a[2].as_str()?, 16).ok()?, a[3].as_bool()?)
+            }
+            "menu_check" if a.len() == 5 => Self::MenuCheck(a[2].as_u64()? as _, a[3].as_u64()? as _, a[4].as_bool()?),
             "fold" if a.len() == 4 => Self::Fold(
                 u32::from_str_radix(a[2].as_str()?, 16).ok()?,
                 u32::from_str_radix(a[3].as_str()?, 16).ok()?,
             ),
+            "frame_jump" if a.len() == 4 => Self::Frame(
+                FrameJump::Relative,
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                a[3].as_i64()? as _,
+            ),
+            "frame_set" if a.len() == 4 => Self::Frame(
+                FrameJump::Absolute,
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                a[3].as_i64()? as _,
+            ),
+            "bookmark_set" if a.len() == 4 => Self::Bookmark(
+                BookmarkOp::Set(a[3].as_u64()? as _),
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+            ),
+            "bookmark_go" if a.len() == 4 => Self::Bookmark(
+                BookmarkOp::Go(a[3].as_u64()? as _),
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+            ),
+            "bookmark_next" if a.len() == 3 => {
+                Self::Bookmark(BookmarkOp::Next, u32::from_str_radix(a[2].as_str()?, 16).ok()?)
+            }
+            "bookmark_prev" if a.len() == 3 => {
+                Self::Bookmark(BookmarkOp::Prev, u32::from_str_radix(a[2].as_str()?, 16).ok()?)
+            }
+            "step" if a.len() == 4 => Self::Step(StepSpec {
+                id: u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                step: a[3].as_f64()?,
+                min: f64::MIN,
+                max: f64::MAX,
+            }),
+            "preset" if a.len() == 3 => {
+                Self::Preset(u32::from_str_radix(a[2].as_str()?, 16).ok()?, TableIndex::Unresolved)
+            }
+            "trackbar_step" if a.len() == 4 => Self::Trackbar(
+                TrackbarOp::Step(a[3].as_i64()? as _),
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+            ),
+            "trackbar_set" if a.len() == 4 => Self::Trackbar(
+                TrackbarOp::Set(a[3].as_i64()? as _),
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+            ),
+            "scroll" if a.len() == 4 => Self::Scroll(
+                ScrollOp::new(a[3].as_str()?)?,
+                WindowTarget::Id(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+            ),
+            "scroll_class" if a.len() == 4 => Self::Scroll(
+                ScrollOp::new(a[3].as_str()?)?,
+                WindowTarget::Class(TableIndex::Unresolved),
+            ),
+            "send_message" | "post_message" if a.len() == 6 => Self::Message(MessageSpec {
+                id: u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                msg: integer(&a[3])? as _,
+                wparam: integer(&a[4])? as _,
+                lparam: integer(&a[5])? as _,
+                post: a[1] == "post_message",
+            }),
+            "click" if a.len() == 5 => Self::Click(
+                WindowTarget::Id(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+                a[3].as_i64()? as _,
+                a[4].as_i64()? as _,
+            ),
+            "click_class" if a.len() == 5 => Self::Click(
+                WindowTarget::Class(TableIndex::Unresolved),
+                a[3].as_i64()? as _,
+                a[4].as_i64()? as _,
+            ),
+            "popup" if a.len() == 5 => Self::Popup(
+                WindowTarget::Id(u32::from_str_radix(a[2].as_str()?, 16).ok()?),
+                a[3].as_i64()? as _,
+                a[4].as_i64()? as _,
+                Some(TableIndex::Unresolved),
+            ),
+            "popup_class" if a.len() == 5 => Self::Popup(
+                WindowTarget::Class(TableIndex::Unresolved),
+                a[3].as_i64()? as _,
+                a[4].as_i64()? as _,
+                Some(TableIndex::Unresolved),
+            ),
+            "view_orbit" if a.len() == 4 => Self::View(ViewOp::Orbit, a[2].as_i64()? as _, a[3].as_i64()? as _),
+            "view_pan" if a.len() == 4 => Self::View(ViewOp::Pan, a[2].as_i64()? as _, a[3].as_i64()? as _),
+            "view_zoom" if a.len() == 3 => Self::View(ViewOp::Zoom, a[2].as_i64()? as _, 0),
+            "snapshot_save" if a.len() == 4 => Self::Snapshot(
+                SnapshotOp::Save,
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                a[3].as_u64()? as _,
+            ),
+            "snapshot_load" if a.len() == 4 => Self::Snapshot(
+                SnapshotOp::Load,
+                u32::from_str_radix(a[2].as_str()?, 16).ok()?,
+                a[3].as_u64()? as _,
+            ),
+            "field_prev" => Self::Field(ComboDir::Prev),
+            "field_next" => Self::Field(ComboDir::Next),
             "kill_focus" => Self::KillFocus,
             "fold_all" => Self::FoldAll,
             "unfold_all" => Self::UnfoldAll,
@@ -59,20 +356,67 @@ impl ItemKind {
     }
 }
 
+/// Optional attributes given as the trailing object of an entry.
+#[derive(Clone, Default, Debug, serde::Deserialize)]
+#[serde(default)]
+pub struct Meta {
+    pub repeat: Option<bool>,
+    pub in_edit: Option<bool>,
+    pub step: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub values: Option<Vec<f64>>,
+    /// The name of the panel that an edit control belongs to, or that a button registers.
+    ///
+    /// `field_prev` and `field_next` cycle through the edit controls of a panel,
+    /// and snapshots save the edit controls of a panel with a register button.
+    pub panel: Option<String>,
+    /// The position of the edit control in `panel`.
+    pub order: Option<u32>,
+    /// The ID of a control in hex that the target window of `scroll_class`, `click_class` and `popup_class`
+    /// is looked for next to, among the windows with the same parent.
+    pub anchor: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Item {
     pub name: String,
+    pub category: String,
     pub kind: ItemKind,
+    pub meta: Meta,
+    /// The class name of the target window of `scroll_class`, `click_class` and `popup_class`.
+    ///
+    /// "Polygon Movie Maker" is the MMD window itself.
+    pub class: Option<String>,
 }
 
 impl Item {
-    fn new(a: &[serde_json::Value]) -> Option<Self> {
+    fn new(category: &str, a: &[serde_json::Value]) -> Option<Self> {
+        let (a, meta) = match a.last() {
+            Some(v @ Value::Object(_)) => (&a[..a.len() - 1], serde_json::from_value(v.clone()).ok()?),
+            _ => (a, Meta::default()),
+        };
         if a.len() < 2 {
             return None;
         }
+        let mut kind = ItemKind::new(a)?;
+        let mut class = None;
+        match &mut kind {
+            ItemKind::Step(spec) => {
+                spec.min = meta.min.unwrap_or(spec.min);
+                spec.max = meta.max.unwrap_or(spec.max);
+            }
+            ItemKind::Scroll(_, WindowTarget::Class(_))
+            | ItemKind::Click(WindowTarget::Class(_), _, _)
+            | ItemKind::Popup(WindowTarget::Class(_), _, _, _) => class = Some(a[2].as_str()?.to_string()),
+            _ => {}
+        }
         Some(Self {
             name: a[0].as_str()?.to_string(),
-            kind: ItemKind::new(a)?,
+            category: category.to_string(),
+            kind,
+            meta,
+            class,
         })
     }
 }
@@ -82,14 +426,14 @@ pub struct MmdMap(Vec<(String, Item)>);
 
 impl MmdMap {
     pub fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
-        fn items(m: &mut Vec<(String, Item)>, v: &Value) -> Option<()> {
+        fn items(m: &mut Vec<(String, Item)>, category: &str, v: &Value) -> Option<()> {
             match v {
                 Value::Object(obj) => {
                     for (key, value) in obj.iter() {
                         if value.is_object() {
-                            items(m, value)?;
+                            items(m, key, value)?;
                         } else if let Some(a) = value.as_array() {
-                            m.push((key.clone(), Item::new(a)?));
+                            m.push((key.clone(), Item::new(category, a)?));
                         }
                     }
                     Some(())
@@ -101,7 +445,7 @@ impl MmdMap {
         let file = File::open(path)?;
         let data: Value = serde_json::from_reader(BufReader::new(file))?;
         let mut m = Vec::new();
-        items(&mut m, &data).ok_or(std::io::ErrorKind::InvalidData)?;
+        items(&mut m, "", &data).ok_or(std::io::ErrorKind::InvalidData)?;
         Ok(Self(m))
     }
 
@@ -129,5 +473,121 @@ mod tests {
         let item = &m.iter().find(|(key, _)| key == "MenuHelpAbout").unwrap().1;
         assert!(item.name == "バージョン情報");
         assert!(matches!(item.kind, ItemKind::Menu(7, 6)));
+        let item = &m.iter().find(|(key, _)| key == "KeyDelete").unwrap().1;
+        assert!(matches!(item.kind, ItemKind::Button(0x1a7)));
+        assert!(item.meta.repeat == Some(false));
+        let item = &m.iter().find(|(key, _)| key == "KillFocus").unwrap().1;
+        assert!(item.meta.in_edit == Some(true));
+        let item = &m.iter().find(|(key, _)| key == "FrameJumpPrev10").unwrap().1;
+        assert!(matches!(item.kind, ItemKind::Frame(FrameJump::Relative, 0x1a1, -10)));
+        let item = &m.iter().find(|(key, _)| key == "MorphEyeFocus").unwrap().1;
+        assert!(item.meta.step == Some(0.1) && item.meta.max == Some(1.0));
+        let item = &m.iter().find(|(key, _)| key == "MorphEyeDown").unwrap().1;
+        assert!(
+            item.kind
+                == ItemKind::Step(StepSpec {
+                    id: 0x1ff,
+                    step: -0.1,
+                    min: 0.0,
+                    max: 1.0
+                })
+        );
+        let item = &m.iter().find(|(key, _)| key == "MorphEyePreset").unwrap().1;
+        assert!(matches!(item.kind, ItemKind::Preset(0x1ff, _)));
+        assert!(item.meta.values == Some(vec![0.0, 0.5, 1.0]));
+        let item = &m.iter().find(|(key, _)| key == "LightSnapshotLoad2").unwrap().1;
+        assert!(item.category == "照明操作");
+        assert!(matches!(item.kind, ItemKind::Snapshot(SnapshotOp::Load, 0x1d4, 2)));
+        let item = &m.iter().find(|(key, _)| key == "LightGreenFocus").unwrap().1;
+        assert!(item.meta.panel.as_deref() == Some("light") && item.meta.order == Some(1));
+        let item = &m.iter().find(|(key, _)| key == "LightRegister").unwrap().1;
+        assert!(item.kind == ItemKind::Button(0x1d4) && item.meta.panel.as_deref() == Some("light"));
+        let item = &m.iter().find(|(key, _)| key == "FieldNext").unwrap().1;
+        assert!(item.kind == ItemKind::Field(ComboDir::Next));
+        assert!(item.meta.in_edit == Some(true));
+        let item = &m.iter().find(|(key, _)| key == "ModelSelectorSelect").unwrap().1;
+        assert!(item.kind == ItemKind::Combo(ComboOp::Select(TableIndex::Unresolved), 0x1b4));
+        let item = &m.iter().find(|(key, _)| key == "ModelShowOff").unwrap().1;
+        assert!(item.kind == ItemKind::ButtonCheck(0x1b7, false));
+        let item = &m.iter().find(|(key, _)| key == "MenuViewFPS60On").unwrap().1;
+        assert!(item.kind == ItemKind::MenuCheck(2, 32, true));
+        let item = &m.iter().find(|(key, _)| key == "TimelineScrollPageRight").unwrap().1;
+        assert!(matches!(
+            item.kind,
+            ItemKind::Scroll(ScrollOp::PageRight, WindowTarget::Class(_))
+        ));
+        assert!(item.class.as_deref() == Some("ScrollBar"));
+        assert!(item.meta.anchor.as_deref() == Some("1a1"));
+        let item = &m.iter().find(|(key, _)| key == "ViewOrbitUp").unwrap().1;
+        assert!(item.kind == ItemKind::View(ViewOp::Orbit, 0, -10));
+        let item = &m.iter().find(|(key, _)| key == "ViewZoomOut").unwrap().1;
+        assert!(item.kind == ItemKind::View(ViewOp::Zoom, -1, 0));
+        let item = &m.iter().find(|(key, _)| key == "ShiftFramePrev").unwrap().1;
+        assert!(
+            item.kind
+                == ItemKind::KeyCombo(KeyComboSpec {
+                    key: 0x25,
+                    ctrl: false,
+                    shift: true,
+                    alt: false
+                })
+        );
+        let item = &m.iter().find(|(key, _)| key == "MorphEyeSliderDown").unwrap().1;
+        assert!(item.kind == ItemKind::Trackbar(TrackbarOp::Step(-10), 0x1fe));
+        let item = &m.iter().find(|(key, _)| key == "BookmarkGo3").unwrap().1;
+        assert!(matches!(item.kind, ItemKind::Bookmark(BookmarkOp::Go(3), 0x1a1)));
+    }
+
+    #[test]
+    fn click_item() {
+        let a: Vec<Value> = serde_json::from_str(r#"[ "クリック", "click_class", "Static", -10, 20 ]"#).unwrap();
+        let item = Item::new("", &a).unwrap();
+        assert!(item.kind == ItemKind::Click(WindowTarget::Class(TableIndex::Unresolved), -10, 20));
+        assert!(item.class.as_deref() == Some("Static"));
+        let a: Vec<Value> = serde_json::from_str(r#"[ "クリック", "click", "1a1", 5, 5 ]"#).unwrap();
+        assert!(ItemKind::new(&a) == Some(ItemKind::Click(WindowTarget::Id(0x1a1), 5, 5)));
+        let a: Vec<Value> = serde_json::from_str(r#"[ "メニュー", "popup_class", "Static", 10, 20 ]"#).unwrap();
+        let item = Item::new("", &a).unwrap();
+        assert!(
+            item.kind
+                == ItemKind::Popup(
+                    WindowTarget::Class(TableIndex::Unresolved),
+                    10,
+                    20,
+                    Some(TableIndex::Unresolved)
+                )
+        );
+        assert!(item.class.as_deref() == Some("Static"));
+    }
+
+    #[test]
+    fn message_kind() {
+        let a: Vec<Value> = serde_json::from_str(r#"[ "メッセージ", "post_message", "1b4", "0x14e", 2, 0 ]"#).unwrap();
+        let spec = MessageSpec {
+            id: 0x1b4,
+            msg: 0x14e,
+            wparam: 2,
+            lparam: 0,
+            post: true,
+        };
+        assert!(ItemKind::new(&a) == Some(ItemKind::Message(spec)));
+        let a: Vec<Value> = serde_json::from_str(r#"[ "メッセージ", "send_message", "1b4", 241, 1, -1 ]"#).unwrap();
+        assert!(matches!(
+            ItemKind::new(&a),
+            Some(ItemKind::Message(MessageSpec {
+                msg: 241,
+                lparam: -1,
+                post: false,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn trackbar_kind() {
+        let a: Vec<Value> = serde_json::from_str(r#"[ "スライダーを増やす", "trackbar_step", "1fe", -5 ]"#).unwrap();
+        assert!(ItemKind::new(&a) == Some(ItemKind::Trackbar(TrackbarOp::Step(-5), 0x1fe)));
+        let a: Vec<Value> = serde_json::from_str(r#"[ "スライダーを最小に", "trackbar_set", "1fe", 0 ]"#).unwrap();
+        assert!(ItemKind::new(&a) == Some(ItemKind::Trackbar(TrackbarOp::Set(0), 0x1fe)));
     }
 }
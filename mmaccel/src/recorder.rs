@@ -0,0 +1,63 @@
+use crate::macros::Step;
+use crate::mmd_map::*;
+use crate::*;
+use std::collections::HashMap;
+
+/// Records actions dispatched by `Handler` and button clicks and menu commands of MMD.
+pub struct Recorder {
+    steps: Vec<Step>,
+    buttons: HashMap<u32, String>,
+    menus: HashMap<u32, String>,
+}
+
+impl Recorder {
+    pub fn new(mmd_map: &MmdMap, mmd_window: HWND) -> Self {
+        let mut buttons = HashMap::new();
+        let mut menus = HashMap::new();
+        for (id, item) in mmd_map.iter() {
+            match item.kind {
+                ItemKind::Button(button) => {
+                    buttons.entry(button).or_insert_with(|| id.clone());
+                }
+                ItemKind::Menu(index, sub_index) => unsafe {
+                    let m = GetSubMenu(GetMenu(mmd_window), index as _);
+                    let command = GetMenuItemID(m, sub_index as _);
+                    if command != u32::MAX {
+                        menus.entry(command).or_insert_with(|| id.clone());
+                    }
+                },
+                _ => {}
+            }
+        }
+        Self {
+            steps: vec![],
+            buttons,
+            menus,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, id: impl AsRef<str>) {
+        log::debug!("Recorder: {}", id.as_ref());
+        self.steps.push(Step::new(id));
+    }
+
+    /// Records a WM_COMMAND observed through the hooks.
+    pub fn command(&mut self, wparam: WPARAM, lparam: LPARAM) {
+        let id = (wparam.0 & 0xffff) as u32;
+        let code = ((wparam.0 >> 16) & 0xffff) as u32;
+        let action = match lparam.0 {
+            0 if code == 0 => self.menus.get(&id),
+            _ if code == BN_CLICKED => self.buttons.get(&id),
+            _ => None,
+        };
+        if let Some(action) = action.cloned() {
+            self.push(action);
+        }
+    }
+
+    #[inline]
+    pub fn finish(self) -> Vec<Step> {
+        self.steps
+    }
+}
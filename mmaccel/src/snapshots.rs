@@ -0,0 +1,70 @@
+use crate::json_map::JsonMap;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The texts of the edit controls of a panel keyed by the control IDs.
+#[derive(Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub values: BTreeMap<u32, String>,
+}
+
+/// Snapshots keyed by the ID of the register button of the panel and the snapshot number.
+#[derive(Debug, Default)]
+pub struct Snapshots(JsonMap<u32, Snapshot>);
+
+impl Snapshots {
+    #[inline]
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self(JsonMap::from_file(path))
+    }
+
+    #[inline]
+    pub fn get(&self, panel: u32, n: u32) -> Option<&Snapshot> {
+        self.0.get(&panel, n)
+    }
+
+    /// Iterates `(panel, n, snapshot)` in the order of the panel and the number.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u32, &Snapshot)> {
+        self.0.iter().map(|(panel, n, snapshot)| (*panel, n, snapshot))
+    }
+
+    /// Sets the values of the snapshot `n`, keeping its name if it already exists.
+    pub fn set(&mut self, panel: u32, n: u32, values: BTreeMap<u32, String>) {
+        self.0.update(panel, n, |snapshot| Snapshot {
+            name: snapshot.map_or_else(|| format!("スナップショット{}", n), |snapshot| snapshot.name),
+            values,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_load() {
+        let path = std::env::temp_dir().join(format!("mmaccel_snapshots_{}.json", std::process::id()));
+        let mut snapshots = Snapshots::from_file(&path);
+        assert!(snapshots.iter().next().is_none());
+        let values = BTreeMap::from([(0x1cd, "0.5".to_string()), (0x1ce, "1".to_string())]);
+        snapshots.set(0x1d4, 2, values.clone());
+        snapshots.0.update(0x1d4, 2, |snapshot| Snapshot {
+            name: "夕方".to_string(),
+            ..snapshot.unwrap()
+        });
+        snapshots.set(0x1d4, 2, values.clone());
+        snapshots.set(0x1c4, 1, BTreeMap::new());
+
+        let loaded = Snapshots::from_file(&path);
+        std::fs::remove_file(&path).ok();
+        let snapshot = loaded.get(0x1d4, 2).unwrap();
+        assert!(snapshot.name == "夕方");
+        assert!(snapshot.values == values);
+        assert!(loaded.get(0x1c4, 1).unwrap().name == "スナップショット1");
+        assert!(loaded.get(0x1c4, 2).is_none());
+        let order = loaded.iter().map(|(panel, n, _)| (panel, n)).collect::<Vec<_>>();
+        assert!(order == vec![(0x1c4, 1), (0x1d4, 2)]);
+    }
+}
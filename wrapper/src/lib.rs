@@ -106,6 +106,24 @@ pub fn enum_windows() -> Vec<HWND> {
     }
 }
 
+/// Enumerates all descendant windows of `parent`.
+#[inline]
+pub fn enum_child_windows(parent: HWND) -> Vec<HWND> {
+    extern "system" fn callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        unsafe {
+            let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+            windows.push(hwnd);
+            true.into()
+        }
+    }
+
+    unsafe {
+        let mut windows = vec![];
+        EnumChildWindows(parent, Some(callback), LPARAM(&mut windows as *mut _ as _));
+        windows
+    }
+}
+
 #[inline]
 pub fn get_window_thread_process_id(hwnd: HWND) -> u32 {
     unsafe {
@@ -127,6 +145,23 @@ pub fn get_class_name(hwnd: HWND) -> String {
     }
 }
 
+#[inline]
+pub fn get_window_text(hwnd: HWND) -> String {
+    unsafe {
+        let mut buffer = vec![0; GetWindowTextLengthW(hwnd) as usize + 1];
+        let size = GetWindowTextW(hwnd, PWSTR(buffer.as_mut_ptr()), buffer.len() as _);
+        String::from_utf16_lossy(&buffer[..size as usize])
+    }
+}
+
+#[inline]
+pub fn set_window_text(hwnd: HWND, text: impl AsRef<str>) {
+    unsafe {
+        let text = to_wchar(text);
+        SetWindowTextW(hwnd, PWSTR(text.as_ptr() as _));
+    }
+}
+
 pub const LVN_ITEMCHANGED: u32 = -101i32 as _;
 pub const LVN_ITEMACTIVATE: u32 = -114i32 as _;
 pub const NM_CLICK: u32 = -2i32 as _;
@@ -135,3 +170,4 @@ pub const NM_RCLICK: u32 = -5i32 as _;
 pub const NM_SETFOCUS: u32 = -7i32 as _;
 pub const NM_KILLFOCUS: u32 = -8i32 as _;
 pub const NM_CUSTOMDRAW: u32 = -12i32 as _;
+pub const TBM_GETPOS: u32 = WM_USER;